@@ -1,20 +1,370 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
+use structopt::clap::{AppSettings, ArgGroup, ArgSettings};
+use jones::joneslib::filters::ClassFilter;
+use jones::joneslib::query::Query;
+use jones::joneslib::diagram::DiagramFormat;
+use jones::joneslib::environments::{python_path, site_packages};
+use jones::joneslib::revisions::verify_revision;
+use jones::joneslib::sources::SearchPath;
 
 #[derive(StructOpt)]
+#[allow(clippy::upper_case_acronyms)]
+#[structopt(setting=AppSettings::SubcommandsNegateReqs)]
+#[structopt(setting=AppSettings::ArgsNegateSubcommands)]
+#[structopt(setting=AppSettings::AllowExternalSubcommands)]
+#[structopt(group=ArgGroup::with_name("matching").args(&["grep", "smart", "method", "type-search"]))]
 pub struct CLI {
 
+    // Commands working on the whole project instead of a single class lookup
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+
     // Flag to search all classes with that value
     #[structopt(short="g", long="grep",
         help="Used to retrieve all classes with that pattern")]
     pub grep: bool,
 
+    // Flag to rank all classes related to the keyword by context
+    #[structopt(short="s", long="smart",
+        help="Rank classes by keyword in name, docstring, methods, bases and module path")]
+    pub smart: bool,
+
+    // Flag to search all classes defining a method with that name
+    #[structopt(short="m", long="method",
+        help="Used to retrieve all classes defining a method with that name or pattern")]
+    pub method: bool,
+
+    // Flag to search all methods taking or returning a type
+    #[structopt(short="t", long="type",
+        help="Used to retrieve all methods accepting or returning that type")]
+    pub type_search: bool,
+
+    // Flag to resolve the full inheritance chain of the class
+    #[structopt(short="a", long="ancestors", conflicts_with_all=&["grep", "smart", "method", "query"],
+        help="Used to show the ancestors of the class resolved across the project files")]
+    pub ancestors: bool,
+
+    // Flag to show every class inheriting the class
+    #[structopt(long="subclasses", conflicts_with_all=&["grep", "smart", "method", "type-search", "ancestors", "query", "doc", "inherited"],
+        help="Used to show the tree of direct and transitive subclasses of the class within the project")]
+    pub subclasses: bool,
+
+    // Flag to show the methods inherited through the method resolution order
+    #[structopt(long="inherited", conflicts_with_all=&["grep", "smart", "method", "type-search", "ancestors", "query", "doc"],
+        help="Used to show the class methods grouped by the ancestor defining them, marking the overrides")]
+    pub inherited: bool,
+
+    // Flag to show the methods called by the class methods and their callers
+    #[structopt(long="calls", conflicts_with_all=&["grep", "smart", "method", "type-search", "ancestors", "subclasses", "inherited", "query", "doc"],
+        help="Used to show the call graph of a class, or of a single method given as Class.method")]
+    pub calls: bool,
+
+    // Render the call graph in Graphviz DOT format
+    #[structopt(long="dot", requires="calls",
+        help="Used with calls to output the call graph in Graphviz DOT format")]
+    pub dot: bool,
+
+    // Flag to inspect module level functions and constants instead of classes
+    #[structopt(long="function", conflicts_with_all=&["grep", "smart", "method", "type-search", "ancestors", "subclasses", "inherited", "calls", "query", "doc"],
+        help="Used to show the module level functions and constants with that name")]
+    pub function: bool,
+
+    // Flag to evaluate a query expression over all classes
+    #[structopt(short="q", long="query", conflicts_with_all=&["grep", "smart", "method", "type-search", "doc"],
+        help="Used to retrieve all classes satisfying a query, e.g. 'name:*Service and not decorator:deprecated'")]
+    pub query: bool,
+
+    // Flag to search a text inside class and method docstrings
+    #[structopt(short="d", long="doc", conflicts_with_all=&["grep", "smart", "method", "type-search"],
+        help="Used to retrieve all class and method docstrings containing that text")]
+    pub doc: bool,
+
+    // Treat the grep keyword as a regular expression
+    #[structopt(short="r", long="regex", requires="matching", conflicts_with_all=&["fuzzy", "type-search"],
+        help="Used with grep, smart or method to match the keyword as a regular expression")]
+    pub regex: bool,
+
+    // Ignore the letter case when matching the grep keyword
+    #[structopt(short="i", long="ignore-case", requires="matching",
+        help="Used with grep, method or type to match without taking the letter case into account")]
+    pub ignore_case: bool,
+
+    // Match the keyword letters in order, allowing gaps between them
+    #[structopt(short="f", long="fuzzy", requires="matching", conflicts_with="type-search",
+        help="Used with grep, smart or method to fuzzy match the keyword, e.g. invsvc finds InvoiceService")]
+    pub fuzzy: bool,
+
+    // Export a class diagram of the class and its ancestors, or of a package
+    #[structopt(long="diagram", possible_values=&["mermaid", "plantuml", "dot"], case_insensitive=true,
+        conflicts_with_all=&["grep", "smart", "method", "type-search", "ancestors", "subclasses", "inherited", "calls", "function", "query", "doc"],
+        help="Used to export a class diagram of the class and its ancestors, or of all the classes of a package")]
+    pub diagram: Option<DiagramFormat>,
+
+    // Keep only the classes inheriting these bases
+    #[structopt(long="base", number_of_values=1, requires="grep",
+        help="Used with grep to keep only the classes inheriting that base class")]
+    pub base: Vec<String>,
+
+    // Keep only the classes decorated with these decorators
+    #[structopt(long="decorator", number_of_values=1, requires="grep",
+        help="Used with grep to keep only the classes decorated with that decorator")]
+    pub decorator: Vec<String>,
+
+    // Keep only the classes created by this metaclass
+    #[structopt(long="metaclass", requires="grep",
+        help="Used with grep to keep only the classes with that metaclass")]
+    pub metaclass: Option<String>,
+
+    // Keep only the classes satisfying this query expression
+    #[structopt(short="w", long="where", requires="grep",
+        help="Used with grep to keep only the classes satisfying that query expression")]
+    pub where_query: Option<String>,
+
+    // How the search directories are read
+    #[structopt(flatten)]
+    pub search: SearchOptions,
+
+    // Read the classes from the git repository at a revision instead of the working directory
+    #[structopt(long="rev", conflicts_with_all=&["env", "pythonpath"],
+        help="Used to read the search directories at a git revision instead of the working directory, e.g. --rev main")]
+    pub rev: Option<String>,
+
     // Class name to be fetched
-    #[structopt(help="Name of the Python class")]
-    pub class_name: String,
-
-    // Search directory
-    #[structopt(parse(from_os_str), default_value=".",
-        help="Search directory")]
-    pub dir_path: PathBuf
-}
\ No newline at end of file
+    #[structopt(set=ArgSettings::Required,
+        help="Name of the Python class or the searched keyword")]
+    pub class_name: Option<String>,
+
+    // Search directories
+    #[structopt(parse(from_os_str),
+        help="Search directories, in priority order [default: .]")]
+    pub dir_paths: Vec<PathBuf>
+}
+#[derive(StructOpt)]
+pub enum Command {
+    /// Find where a class is imported, instantiated, checked, annotated or subclassed
+    #[structopt(name="usages")]
+    Usages {
+        // Class name to be searched
+        #[structopt(help="Name of the Python class or its dotted path")]
+        class_name: String,
+
+        // How the search directories are read
+        #[structopt(flatten)]
+        search: SearchOptions,
+
+        // Git revision the project is read at
+        #[structopt(long="rev", conflicts_with_all=&["env", "pythonpath"],
+            help="Used to read the project at a git revision instead of the working directory")]
+        rev: Option<String>,
+
+        // Search directories
+        #[structopt(parse(from_os_str),
+            help="Search directories, in priority order [default: .]")]
+        dir_paths: Vec<PathBuf>
+    },
+
+    /// Print everything defined in a module or package: classes, functions, constants, __all__ and imports
+    #[structopt(name="module")]
+    Module {
+        // Module to be inspected
+        #[structopt(help="Path of the module file or package, or its dotted name")]
+        module: String,
+
+        // How the search directories are read
+        #[structopt(flatten)]
+        search: SearchOptions,
+
+        // Git revision the project is read at
+        #[structopt(long="rev", conflicts_with_all=&["env", "pythonpath"],
+            help="Used to read the project at a git revision instead of the working directory")]
+        rev: Option<String>,
+
+        // Search directories
+        #[structopt(parse(from_os_str),
+            help="Search directories, in priority order [default: .]")]
+        dir_paths: Vec<PathBuf>
+    },
+
+    /// Compare the API of a class between two git revisions or two directories
+    #[structopt(name="diff")]
+    Diff {
+        // Class name to be compared
+        #[structopt(help="Name of the Python class or its dotted path")]
+        class_name: String,
+
+        // Old version of the class
+        #[structopt(long="from",
+            help="Git revision or directory holding the old version of the class")]
+        from: String,
+
+        // New version of the class
+        #[structopt(long="to",
+            help="Git revision or directory holding the new version of the class [default: the working directory]")]
+        to: Option<String>,
+
+        // Search directories
+        #[structopt(parse(from_os_str),
+            help="Search directories, in priority order [default: .]")]
+        dir_paths: Vec<PathBuf>
+    },
+
+    /// Compare the public API of the project between two git revisions or two directories, exiting with 1 on breaking changes
+    #[structopt(name="breaking")]
+    Breaking {
+        // Old version of the project
+        #[structopt(long="from",
+            help="Git revision or directory holding the old version of the project")]
+        from: String,
+
+        // New version of the project
+        #[structopt(long="to",
+            help="Git revision or directory holding the new version of the project [default: the working directory]")]
+        to: Option<String>,
+
+        // Machine readable report
+        #[structopt(long="json", help="Used to print the report as JSON")]
+        json: bool,
+
+        // Search directories
+        #[structopt(parse(from_os_str),
+            help="Search directories, in priority order [default: .]")]
+        dir_paths: Vec<PathBuf>
+    },
+
+    /// Walk the git log of the class file to find when each method was introduced and when its signature last changed
+    #[structopt(name="history")]
+    History {
+        // Class name to be searched
+        #[structopt(help="Name of the Python class or its dotted path")]
+        class_name: String,
+
+        // How the search directories are read
+        #[structopt(flatten)]
+        search: SearchOptions,
+
+        // Search directories
+        #[structopt(parse(from_os_str),
+            help="Search directories, in priority order [default: .]")]
+        dir_paths: Vec<PathBuf>
+    }
+}
+
+/// The flags adding directories to the search path and reading archives,
+/// shared by the class lookup and the commands
+#[derive(StructOpt)]
+pub struct SearchOptions {
+
+    // Search the site-packages of a virtual environment after the search directories
+    #[structopt(long="env", parse(from_os_str),
+        help="Used to search the site-packages of a virtual environment, e.g. --env .venv requests.Session")]
+    pub env: Option<PathBuf>,
+
+    // Search the directories listed in PYTHONPATH after the given ones
+    #[structopt(long="pythonpath",
+        help="Used to also search the directories listed in the PYTHONPATH environment variable")]
+    pub pythonpath: bool,
+
+    // Read the archives found in the search directories
+    #[structopt(long="archives", conflicts_with="rev",
+        help="Used to also read the wheels, zip archives and sdists found in the search directories")]
+    pub archives: bool
+}
+impl SearchOptions {
+    /// The directories to be searched in priority order, like Python's module
+    /// search path: the given directories or the current one, then
+    /// `PYTHONPATH`, then the `site-packages` of the virtual environment
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_paths` - The search directories given on the command line
+    /// * `revision` - The git revision the directories are read at, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the environment has no `site-packages`, if a
+    /// search directory does not exist or if the revision is not found in the
+    /// repository of a search directory
+    pub fn search_path(&self, dir_paths: &[PathBuf], revision: Option<&str>) -> Result<SearchPath, String> {
+        let mut roots = default_dir_paths(dir_paths);
+        if self.pythonpath {
+            // Like Python, the entries which do not exist are skipped
+            roots.extend(python_path().into_iter().filter(|entry| entry.exists()));
+        }
+        if let Some(env_path) = &self.env {
+            roots.push(site_packages(env_path)?);
+        }
+        search_path(&roots, revision, self.archives)
+    }
+}
+
+impl CLI {
+    /// The directories searched for the class, read at the revision if one is given
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the search path could not be built
+    pub fn search_path(&self) -> Result<SearchPath, String> {
+        self.search.search_path(&self.dir_paths, self.rev.as_deref())
+    }
+
+    /// Builds the filter of the grep results out of the filter flags
+    ///
+    /// # Errors
+    ///
+    /// Returns the syntax error of the `--where` query expression
+    pub fn grep_filter(&self) -> Result<ClassFilter, String> {
+        let query = match &self.where_query {
+            Some(expression) => Some(Query::parse(expression)?),
+            None => None
+        };
+        Ok(ClassFilter::new(self.base.clone(), self.decorator.clone(), self.metaclass.clone(), query))
+    }
+}
+
+/// The search path of one side of a diff, which is a directory when one
+/// exists at that path and otherwise a revision of the search directories
+///
+/// # Errors
+///
+/// Returns an error if the revision is not found in the repository of a search directory
+pub fn version_search_path(version: Option<&str>, dir_paths: &[PathBuf]) -> Result<SearchPath, String> {
+    match version {
+        Some(directory) if Path::new(directory).is_dir() => search_path(&[PathBuf::from(directory)], None, false),
+        Some(revision) => search_path(dir_paths, Some(revision), false),
+        None => search_path(dir_paths, None, false)
+    }
+}
+
+/// The search directories, or the current directory if none is given,
+/// read at the revision if one is given and reading the archives found
+/// in them if asked to
+///
+/// # Errors
+///
+/// Returns an error naming each search directory which does not exist, or
+/// if the revision is not found in the repository of a search directory
+pub fn search_path(dir_paths: &[PathBuf], revision: Option<&str>, archives: bool) -> Result<SearchPath, String> {
+    let roots = default_dir_paths(dir_paths);
+    let missing: Vec<String> = roots.iter()
+        .filter(|root| !root.exists())
+        .map(|root| format!("Could not read {}", root.display()))
+        .collect();
+    if !missing.is_empty() {
+        return Err(missing.join("\n"))
+    }
+    if let Some(revision) = revision {
+        for root in roots.iter() {
+            verify_revision(root, revision)?;
+        }
+    }
+    Ok(SearchPath::new(roots, revision.map(String::from), archives))
+}
+
+/// The given search directories, or the current directory if none is given
+fn default_dir_paths(dir_paths: &[PathBuf]) -> Vec<PathBuf> {
+    match dir_paths.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => dir_paths.to_vec()
+    }
+}
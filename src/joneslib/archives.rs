@@ -0,0 +1,202 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use flate2::read::GzDecoder;
+use super::{
+    is_python_file, is_notebook_file,
+    notebooks::read_notebook,
+    sources::SourceFile
+};
+
+const MEMBER_SEPARATOR: &str = "!/";
+const ZIP_EXTENSIONS: [&str; 2] = [".whl", ".zip"];
+const TARBALL_EXTENSIONS: [&str; 2] = [".tar.gz", ".tgz"];
+const SOURCE_LAYOUT: &str = "src/";
+
+/// Checks if the file is a wheel, a zip archive or a gzipped tarball
+pub fn is_archive(file_path: &Path) -> bool {
+    is_zip(&file_path.to_string_lossy()) || is_tarball(&file_path.to_string_lossy())
+}
+
+fn is_zip(path: &str) -> bool {
+    ZIP_EXTENSIONS.iter().any(|extension| path.ends_with(extension))
+}
+
+fn is_tarball(path: &str) -> bool {
+    TARBALL_EXTENSIONS.iter().any(|extension| path.ends_with(extension))
+}
+
+/// Reads the Python files, stubs and notebooks packed inside an archive,
+/// without unpacking it on disk
+///
+/// Every member is named `archive.whl!/pkg/module.py`, the path of the
+/// archive followed by the path inside it.
+///
+/// # Arguments
+///
+/// * `archive_path` - The `.whl`, `.zip`, `.tar.gz` or `.tgz` file
+///
+/// # Output
+///
+/// * `None` - if the archive could not be read
+/// * `Some` - the readable Python members in archive order
+pub fn read_archive(archive_path: &Path) -> Option<Vec<SourceFile>> {
+    let path = archive_path.to_string_lossy().to_string();
+    let file = File::open(archive_path).ok()?;
+    let members = match is_tarball(&path) {
+        true => read_tarball(file)?,
+        false => read_zip(file)?
+    };
+
+    let mut sources: Vec<SourceFile> = Vec::new();
+    for (member, content) in members {
+        let member_path = format!("{}{}{}", path, MEMBER_SEPARATOR, member);
+        match is_notebook_file(Path::new(&member)) {
+            true => sources.extend(read_notebook(member_path, &content)),
+            false => sources.push(SourceFile::new(member_path, content))
+        }
+    }
+    Some(sources)
+}
+
+/// Whether an archive member is read, by its path inside the archive
+fn is_source_member(member: &str) -> bool {
+    is_python_file(Path::new(member)) || is_notebook_file(Path::new(member))
+}
+
+fn read_zip(file: File) -> Option<Vec<(String, String)>> {
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let mut members: Vec<(String, String)> = Vec::new();
+
+    for position in 0..archive.len() {
+        let mut entry = match archive.by_index(position) {
+            Ok(entry) => entry,
+            Err(_) => continue
+        };
+        if !entry.is_file() || !is_source_member(entry.name()) {
+            continue
+        }
+        let name = entry.name().to_string();
+        let mut content = String::new();
+        if entry.read_to_string(&mut content).is_ok() {
+            members.push((name, content));
+        }
+    }
+    Some(members)
+}
+
+fn read_tarball(file: File) -> Option<Vec<(String, String)>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut members: Vec<(String, String)> = Vec::new();
+
+    for entry in archive.entries().ok()? {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue
+        };
+        let name = match entry.path() {
+            Ok(name) => name.to_string_lossy().to_string(),
+            Err(_) => continue
+        };
+        if !entry.header().entry_type().is_file() || !is_source_member(&name) {
+            continue
+        }
+        let mut content = String::new();
+        if entry.read_to_string(&mut content).is_ok() {
+            members.push((name, content));
+        }
+    }
+    Some(members)
+}
+
+/// The path a member of an archive is imported by, relative to the root of
+/// the packed project
+///
+/// Wheels and zip archives hold the packages at their root, while source
+/// distributions nest them under a `name-version/` directory and sometimes
+/// under `src/`.
+///
+/// # Output
+///
+/// * `None` - if the path does not point inside an archive
+/// * `Some` - e.g. `pkg/module.py` for `shop-1.0.tar.gz!/shop-1.0/src/pkg/module.py`
+pub fn member_import_path(path: &str) -> Option<&str> {
+    let (archive, member) = path.split_once(MEMBER_SEPARATOR)?;
+    if !is_tarball(archive) {
+        return Some(member)
+    }
+    let nested = member.split_once('/').map(|(_, nested)| nested).unwrap_or(member);
+    Some(nested.strip_prefix(SOURCE_LAYOUT).unwrap_or(nested))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    static PYTHON_CODE: &str = "class Cart:\n    pass\n";
+
+    #[test]
+    fn test_read_zip_archive() {
+        let test_dir = "./testing_zip_archive";
+        fs::create_dir_all(test_dir).expect("Could not write dir");
+        let archive_path = format!("{}/shop-1.0-py3-none-any.whl", test_dir);
+
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        writer.start_file("shop/cart.py", options).unwrap();
+        writer.write_all(PYTHON_CODE.as_bytes()).unwrap();
+        writer.start_file("shop-1.0.dist-info/METADATA", options).unwrap();
+        writer.write_all(b"Name: shop").unwrap();
+        writer.finish().unwrap();
+
+        let sources = read_archive(Path::new(&archive_path));
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        assert_eq!(sources, Some(vec![SourceFile::new(
+            format!("{}!/shop/cart.py", archive_path), PYTHON_CODE.to_string()
+        )]));
+    }
+
+    #[test]
+    fn test_read_tarball() {
+        let test_dir = "./testing_tarball";
+        fs::create_dir_all(test_dir).expect("Could not write dir");
+        let archive_path = format!("{}/shop-1.0.tar.gz", test_dir);
+
+        let encoder = GzEncoder::new(File::create(&archive_path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(PYTHON_CODE.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "shop-1.0/src/shop/cart.py", PYTHON_CODE.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let sources = read_archive(Path::new(&archive_path)).unwrap();
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].path, format!("{}!/shop-1.0/src/shop/cart.py", archive_path));
+        assert_eq!(member_import_path(&sources[0].path), Some("shop/cart.py"));
+    }
+
+    #[test]
+    fn test_member_import_path() {
+        assert_eq!(member_import_path("./wheelhouse/shop.whl!/shop/__init__.py"), Some("shop/__init__.py"));
+        assert_eq!(member_import_path("./wheelhouse/shop-1.0.tgz!/shop-1.0/shop/cart.py"), Some("shop/cart.py"));
+        assert_eq!(member_import_path("./shop/cart.py"), None);
+    }
+}
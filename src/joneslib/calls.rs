@@ -0,0 +1,369 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use regex::Regex;
+use super::{
+    hierarchy::{linearize, MroEntry},
+    objects::IndexedClass,
+    project::Project,
+    utils::{extract_attributes, strip_comment}
+};
+
+const DEFAULT_TYPE: &str = "None";
+const SUPER_CALL: &str = "super()";
+const INSTANCE_RECEIVERS: [&str; 2] = ["self", "cls"];
+const OPTIONAL_TYPE: &str = "Optional[";
+const DOT_INDENT: &str = "    ";
+
+static CALL_SITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"([A-Za-z_]\w*(?:\(\))?(?:\.[A-Za-z_]\w*)*)\.([A-Za-z_]\w*)\s*\(").unwrap()
+});
+static SELF_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bself\.([A-Za-z_]\w*)\s*=\s*([A-Za-z_]\w*)\s*$").unwrap()
+});
+
+/// A method of the project, identified by its class and its name
+pub type MethodId = (usize, String);
+
+/// A method call written in the body of another method
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Call {
+    pub written: String,
+    pub callee: Option<MethodId>,
+    pub location: String
+}
+
+/// A call together with the calls made by the called method
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct CallNode {
+    pub name: String,
+    pub written: String,
+    pub location: String,
+    pub children: Vec<CallNode>,
+    pub repeated: bool
+}
+
+/// The calls made by every method of the project, resolved to the called
+/// methods when the receiver is `self`, `cls`, `super()`, a typed attribute
+/// of `self` or a class of the project
+#[derive(Debug)]
+pub struct CallGraph {
+    calls: HashMap<MethodId, Vec<Call>>
+}
+impl CallGraph {
+    pub fn new(project: &Project) -> Self {
+        let mut calls: HashMap<MethodId, Vec<Call>> = HashMap::new();
+
+        for (class_id, indexed) in project.classes.iter().enumerate() {
+            let mro = linearize(project, class_id).unwrap_or_else(|_| vec![MroEntry::Class(class_id)]);
+            let attribute_types = attribute_types(project, class_id);
+            for method in indexed.class.methods.iter() {
+                let method_line = indexed.method_line(&method.name);
+                let mut method_calls: Vec<Call> = Vec::new();
+
+                for (offset, line) in indexed.method_code(&method.name).iter().enumerate().skip(1) {
+                    let code = strip_comment(line);
+                    for captures in CALL_SITE.captures_iter(code) {
+                        let (receiver, called) = (&captures[1], &captures[2]);
+                        let callee = resolve_call(project, (class_id, &attribute_types), &mro, receiver, called);
+                        if callee.is_none() && !is_instance_receiver(receiver) {
+                            continue
+                        }
+                        method_calls.push(Call {
+                            written: format!("{}.{}", receiver, called),
+                            callee,
+                            location: format!("{}:{}", indexed.file_path, method_line + offset)
+                        });
+                    }
+                }
+                calls.insert((class_id, method.name.clone()), method_calls);
+            }
+        }
+        CallGraph {
+            calls
+        }
+    }
+
+    /// The calls written in the body of a method
+    pub fn calls(&self, method: &MethodId) -> &[Call] {
+        self.calls.get(method).map(|calls| calls.as_slice()).unwrap_or(&[])
+    }
+
+    /// The methods calling a method, each one with the call it makes
+    pub fn callers(&self, method: &MethodId) -> Vec<(MethodId, &Call)> {
+        let mut callers: Vec<(MethodId, &Call)> = self.calls.iter()
+            .flat_map(|(caller, calls)| calls.iter()
+                .filter(|call| call.callee.as_ref() == Some(method))
+                .map(move |call| (caller.clone(), call)))
+            .collect();
+        callers.sort_by(|first, second| first.1.location.cmp(&second.1.location));
+        callers
+    }
+
+    /// Builds the tree of calls made by a method, following the resolved
+    /// callees. Methods already expanded elsewhere in the tree are marked as
+    /// repeated instead of being expanded again.
+    pub fn call_tree(&self, project: &Project, method: &MethodId) -> Vec<CallNode> {
+        let mut expanded: HashSet<MethodId> = HashSet::new();
+        expanded.insert(method.clone());
+        self.call_nodes(project, method, &mut expanded)
+    }
+
+    fn call_nodes(&self, project: &Project, method: &MethodId, expanded: &mut HashSet<MethodId>) -> Vec<CallNode> {
+        let mut nodes: Vec<CallNode> = Vec::new();
+        for call in self.calls(method).iter() {
+            let (name, children, repeated) = match &call.callee {
+                Some(callee) => {
+                    let repeated = !expanded.insert(callee.clone());
+                    let children = match repeated {
+                        true => Vec::new(),
+                        false => self.call_nodes(project, callee, expanded)
+                    };
+                    (method_name(project, callee), children, repeated)
+                },
+                None => (call.written.clone(), Vec::new(), false)
+            };
+            nodes.push(CallNode {
+                name,
+                written: call.written.clone(),
+                location: call.location.clone(),
+                children,
+                repeated
+            });
+        }
+        nodes
+    }
+
+    /// Renders the calls reachable from the methods, and the calls made to
+    /// them, as a Graphviz DOT graph. Unresolved calls are drawn dashed.
+    pub fn render_dot(&self, project: &Project, methods: &[MethodId]) -> String {
+        let mut edges: Vec<String> = Vec::new();
+        let mut visited: HashSet<MethodId> = HashSet::new();
+        let mut pending: Vec<MethodId> = methods.to_vec();
+
+        while let Some(method) = pending.pop() {
+            if !visited.insert(method.clone()) {
+                continue
+            }
+            let caller = method_name(project, &method);
+            for call in self.calls(&method).iter() {
+                let edge = match &call.callee {
+                    Some(callee) => {
+                        pending.push(callee.clone());
+                        format!("\"{}\" -> \"{}\";", caller, method_name(project, callee))
+                    },
+                    None => format!("\"{}\" -> \"{}\" [style=dashed];", caller, call.written)
+                };
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+        for method in methods.iter() {
+            for (caller, _) in self.callers(method) {
+                let edge = format!("\"{}\" -> \"{}\";", method_name(project, &caller), method_name(project, method));
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+
+        let mut lines: Vec<String> = vec![String::from("digraph calls {"), format!("{}rankdir=LR;", DOT_INDENT)];
+        lines.extend(edges.iter().map(|edge| format!("{}{}", DOT_INDENT, edge)));
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+}
+
+/// The method written as `Class.method`
+pub fn method_name(project: &Project, method: &MethodId) -> String {
+    format!("{}.{}", project.classes[method.0].class.name, method.1)
+}
+
+fn is_instance_receiver(receiver: &str) -> bool {
+    receiver == SUPER_CALL || INSTANCE_RECEIVERS.iter()
+        .any(|instance| receiver == *instance || receiver.starts_with(&format!("{}.", instance)))
+}
+
+/// Resolves the method called on a receiver from a method of a class,
+/// given with the types of its attributes
+fn resolve_call(
+    project: &Project,
+    (class_id, attribute_types): (usize, &HashMap<String, String>),
+    mro: &[MroEntry],
+    receiver: &str,
+    called: &str
+) -> Option<MethodId> {
+    if INSTANCE_RECEIVERS.contains(&receiver) {
+        return find_method(project, mro, called)
+    }
+    if receiver == SUPER_CALL {
+        return find_method(project, mro.get(1..).unwrap_or(&[]), called)
+    }
+
+    let target_class = match receiver.strip_prefix("self.") {
+        Some(attribute) if !attribute.contains('.') => {
+            project.resolve(project.module_id_of(class_id), attribute_types.get(attribute)?)?
+        },
+        Some(_) => return None,
+        None => project.resolve(project.module_id_of(class_id), receiver)?
+    };
+    let target_mro = linearize(project, target_class).unwrap_or_else(|_| vec![MroEntry::Class(target_class)]);
+    find_method(project, &target_mro, called)
+}
+
+/// The first class of a resolution order defining the method
+fn find_method(project: &Project, mro: &[MroEntry], called: &str) -> Option<MethodId> {
+    mro.iter()
+        .find_map(|entry| match entry {
+            MroEntry::Class(class_id) if project.classes[*class_id].class.methods.iter()
+                .any(|method| method.name == called) => Some(*class_id),
+            _ => None
+        })
+        .map(|class_id| (class_id, called.to_string()))
+}
+
+/// The types of the attributes of a class, taken from their annotation or
+/// from the annotation of the method parameter assigned to them
+///
+/// # Example
+/// `self.repo` is a `Repository` when `__init__(self, repo: Repository)`
+/// assigns `self.repo = repo`
+fn attribute_types(project: &Project, class_id: usize) -> HashMap<String, String> {
+    let indexed = &project.classes[class_id];
+    let mut assigned: HashMap<String, String> = HashMap::new();
+    for line in indexed.code.iter() {
+        if let Some(captures) = SELF_ASSIGNMENT.captures(line.trim_end()) {
+            assigned.entry(captures[1].to_string()).or_insert_with(|| captures[2].to_string());
+        }
+    }
+
+    let mut types: HashMap<String, String> = HashMap::new();
+    for attribute in extract_attributes(&indexed.code) {
+        let static_type = match attribute.static_type != DEFAULT_TYPE {
+            true => attribute.static_type,
+            false => match assigned.get(&attribute.name).and_then(|parameter| parameter_type(indexed, parameter)) {
+                Some(static_type) => static_type,
+                None => continue
+            }
+        };
+        let static_type = match static_type.strip_prefix(OPTIONAL_TYPE) {
+            Some(inner) => inner.trim_end_matches(']').to_string(),
+            None => static_type
+        };
+        types.insert(attribute.name, static_type);
+    }
+    types
+}
+
+/// The annotation of a method parameter of a class, without its default value
+fn parameter_type(indexed: &IndexedClass, name: &str) -> Option<String> {
+    indexed.class.methods.iter()
+        .flat_map(|method| method.parameters.iter())
+        .find(|parameter| parameter.name == name && parameter.static_type != DEFAULT_TYPE)
+        .and_then(|parameter| parameter.static_type.split('=').next())
+        .map(|static_type| static_type.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::sources::SourceFile;
+
+    fn billing_project() -> Project {
+        let sources = vec![
+            SourceFile::new(String::from("./billing/repository.py"), String::from(
+"class Repository:
+    def save(self, item):
+        self.validate(item)
+
+    def validate(self, item):
+        pass
+"
+            )),
+            SourceFile::new(String::from("./billing/invoices.py"), String::from(
+"from .repository import Repository
+
+class BaseService:
+    def close(self):
+        self.flush()
+
+class InvoiceService(BaseService):
+    def __init__(self, repo: Optional[Repository] = None):
+        self.repo = repo
+
+    def create(self, invoice):
+        self.repo.save(invoice)  # self.ignored()
+        self.notify(\"#{}\".format(invoice.number), invoice)
+
+    def close(self):
+        super().close()
+        Repository.validate(None)
+"
+            )),
+        ];
+        Project::new(Path::new("."), sources)
+    }
+
+    fn method(project: &Project, class_name: &str, method_name: &str) -> MethodId {
+        (project.find_class(class_name).unwrap(), method_name.to_string())
+    }
+
+    #[test]
+    fn test_call_graph_calls() {
+        let project = billing_project();
+        let graph = CallGraph::new(&project);
+        let calls = graph.calls(&method(&project, "InvoiceService", "create"));
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].written, "self.repo.save");
+        assert_eq!(calls[0].callee, Some(method(&project, "Repository", "save")));
+        assert_eq!(calls[0].location, "./billing/invoices.py:12");
+        assert_eq!(calls[1].written, "self.notify");
+        assert_eq!(calls[1].callee, None);
+
+        let calls = graph.calls(&method(&project, "InvoiceService", "close"));
+        assert_eq!(calls[0].callee, Some(method(&project, "BaseService", "close")));
+        assert_eq!(calls[1].callee, Some(method(&project, "Repository", "validate")));
+    }
+
+    #[test]
+    fn test_call_graph_callers_and_tree() {
+        let project = billing_project();
+        let graph = CallGraph::new(&project);
+
+        let callers = graph.callers(&method(&project, "Repository", "validate"));
+        let names: Vec<String> = callers.iter().map(|(caller, _)| method_name(&project, caller)).collect();
+        assert_eq!(names, vec!["InvoiceService.close", "Repository.save"]);
+
+        let tree = graph.call_tree(&project, &method(&project, "InvoiceService", "create"));
+        assert_eq!(tree[0].name, "Repository.save");
+        assert_eq!(tree[0].children[0].name, "Repository.validate");
+        assert_eq!(tree[1].name, "self.notify");
+        assert!(tree[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_call_graph_dot() {
+        let project = billing_project();
+        let graph = CallGraph::new(&project);
+        let dot = graph.render_dot(&project, &[method(&project, "InvoiceService", "create")]);
+
+        assert!(dot.starts_with("digraph calls {\n    rankdir=LR;\n"));
+        assert!(dot.contains("    \"InvoiceService.create\" -> \"Repository.save\";\n"));
+        assert!(dot.contains("    \"InvoiceService.create\" -> \"self.notify\" [style=dashed];\n"));
+        assert!(dot.contains("    \"Repository.save\" -> \"Repository.validate\";\n"));
+    }
+}
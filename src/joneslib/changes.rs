@@ -0,0 +1,461 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use serde_json::{json, Value};
+use super::{
+    objects::{ApiChange, ClassChanges, Method, Parameter, PythonClass},
+    project::{is_nested, Project}
+};
+
+const DEFAULT_TYPE: &str = "None";
+const DEFAULT_SEPARATOR: char = '=';
+const VARIADIC_MARKER: char = '*';
+const PRIVATE_PREFIX: char = '_';
+const DUNDER: &str = "__";
+
+/// A parameter split into its name, annotation and default value, which
+/// are written together by the parameter parser
+#[derive(Debug)]
+#[derive(PartialEq)]
+struct ParameterApi {
+    name: String,
+    static_type: Option<String>,
+    default: Option<String>
+}
+impl ParameterApi {
+    fn new(parameter: &Parameter) -> Self {
+        let (name, name_default) = split_default(&parameter.name);
+        let (static_type, type_default) = split_default(&parameter.static_type);
+        let static_type = match static_type.is_empty() || static_type == DEFAULT_TYPE {
+            true => None,
+            false => Some(static_type)
+        };
+
+        ParameterApi {
+            name,
+            static_type,
+            default: name_default.or(type_default)
+        }
+    }
+
+    /// If the callers have to pass the parameter
+    fn is_required(&self) -> bool {
+        self.default.is_none() && !self.name.starts_with(VARIADIC_MARKER)
+    }
+
+    /// The parameter as written in a signature, e.g. `retries: int = 3`
+    fn written(&self) -> String {
+        let mut written = self.name.clone();
+        if let Some(static_type) = &self.static_type {
+            written.push_str(&format!(": {}", static_type));
+        }
+        if let Some(default) = &self.default {
+            written.push_str(&format!(" = {}", default));
+        }
+        written
+    }
+}
+
+/// Splits `text = default` into the text and its default value
+fn split_default(written: &str) -> (String, Option<String>) {
+    match written.split_once(DEFAULT_SEPARATOR) {
+        Some((text, default)) => (text.trim().to_string(), Some(default.trim().to_string())),
+        None => (written.trim().to_string(), None)
+    }
+}
+
+/// If a name is part of the public API: the names starting with an underscore
+/// are private, except for the dunder methods such as `__init__`
+pub fn is_public(name: &str) -> bool {
+    let name = name.trim_start_matches(VARIADIC_MARKER);
+    !name.starts_with(PRIVATE_PREFIX) || (name.starts_with(DUNDER) && name.ends_with(DUNDER))
+}
+
+/// Compares the API of two versions of a class: the base classes, the added
+/// and removed methods, and for the methods found in both the parameters,
+/// their order, annotations and default values and the return type
+///
+/// The parameters are paired by name. A parameter of the old version with no
+/// pair, standing at the same position as an unpaired parameter of the new
+/// version, is reported as renamed.
+///
+/// # Arguments
+///
+/// * `old` - The class before the change
+/// * `new` - The class after the change
+///
+/// # Output
+///
+/// The changes in the order of the new class methods, empty if the API is the same
+pub fn diff_classes(old: &PythonClass, new: &PythonClass) -> Vec<ApiChange> {
+    compare_classes(old, new, |_| true)
+}
+
+/// Compares only the public methods and parameters of two versions of a class
+pub fn diff_public_classes(old: &PythonClass, new: &PythonClass) -> Vec<ApiChange> {
+    compare_classes(old, new, is_public)
+}
+
+fn compare_classes(old: &PythonClass, new: &PythonClass, visible: fn(&str) -> bool) -> Vec<ApiChange> {
+    let mut changes: Vec<ApiChange> = Vec::new();
+
+    if old.inheritance != new.inheritance {
+        changes.push(ApiChange::BasesChanged { from: old.inheritance.clone(), to: new.inheritance.clone() });
+    }
+    for method in old.methods.iter().filter(|method| visible(&method.name)) {
+        if find_method(new, &method.name).is_none() {
+            changes.push(ApiChange::MethodRemoved { signature: method.signature() });
+        }
+    }
+    for method in new.methods.iter().filter(|method| visible(&method.name)) {
+        match find_method(old, &method.name) {
+            Some(old_method) => changes.extend(diff_methods(old_method, method, visible)),
+            None => changes.push(ApiChange::MethodAdded { signature: method.signature() })
+        }
+    }
+    changes
+}
+
+fn find_method<'a>(class: &'a PythonClass, name: &str) -> Option<&'a Method> {
+    class.methods.iter().find(|method| method.name == name)
+}
+
+fn diff_methods(old: &Method, new: &Method, visible: fn(&str) -> bool) -> Vec<ApiChange> {
+    let method = new.name.clone();
+    let parameters = |method: &Method| -> Vec<ParameterApi> {
+        method.parameters.iter().map(ParameterApi::new).collect()
+    };
+    let mut changes: Vec<ApiChange> = Vec::new();
+    if let Some(reordered) = reordered_parameters(&method, &parameters(old), &parameters(new)) {
+        changes.push(reordered);
+    }
+
+    let old_parameters: Vec<ParameterApi> = parameters(old).into_iter().filter(|parameter| visible(&parameter.name)).collect();
+    let new_parameters: Vec<ParameterApi> = parameters(new).into_iter().filter(|parameter| visible(&parameter.name)).collect();
+    let paired = |parameters: &[ParameterApi], name: &str| parameters.iter().any(|parameter| parameter.name == name);
+
+    let mut renamed: Vec<usize> = Vec::new();
+    for (position, parameter) in new_parameters.iter().enumerate() {
+        let previous = match old_parameters.iter().find(|old_parameter| old_parameter.name == parameter.name) {
+            Some(previous) => previous,
+            None => match old_parameters.get(position) {
+                Some(previous) if !paired(&new_parameters, &previous.name) => {
+                    changes.push(ApiChange::ParameterRenamed {
+                        method: method.clone(), from: previous.name.clone(), to: parameter.name.clone()
+                    });
+                    renamed.push(position);
+                    previous
+                },
+                _ => {
+                    changes.push(ApiChange::ParameterAdded {
+                        method: method.clone(), parameter: parameter.written(), required: parameter.is_required()
+                    });
+                    continue
+                }
+            }
+        };
+        if previous.static_type != parameter.static_type {
+            changes.push(ApiChange::TypeChanged {
+                method: method.clone(),
+                parameter: parameter.name.clone(),
+                from: previous.static_type.clone().unwrap_or_else(|| DEFAULT_TYPE.to_string()),
+                to: parameter.static_type.clone().unwrap_or_else(|| DEFAULT_TYPE.to_string())
+            });
+        }
+        if previous.default != parameter.default {
+            changes.push(ApiChange::DefaultChanged {
+                method: method.clone(),
+                parameter: parameter.name.clone(),
+                from: previous.default.clone(),
+                to: parameter.default.clone()
+            });
+        }
+    }
+    for (position, parameter) in old_parameters.iter().enumerate() {
+        if !paired(&new_parameters, &parameter.name) && !renamed.contains(&position) {
+            changes.push(ApiChange::ParameterRemoved { method: method.clone(), parameter: parameter.written() });
+        }
+    }
+
+    if old.output != new.output {
+        changes.push(ApiChange::ReturnTypeChanged { method, from: old.output.clone(), to: new.output.clone() });
+    }
+    changes
+}
+
+/// The positional parameters of both versions, when a parameter kept in the
+/// new version is not passed at the same position anymore
+///
+/// Only the parameters written before the first `*` marker can be passed by
+/// position, so the keyword only ones are free to move. A parameter added or
+/// removed before a kept one shifts it as well, and the private parameters
+/// count since they take a position all the same.
+fn reordered_parameters(method: &str, old: &[ParameterApi], new: &[ParameterApi]) -> Option<ApiChange> {
+    let positional = |parameters: &[ParameterApi]| -> Vec<String> {
+        parameters.iter()
+            .take_while(|parameter| !parameter.name.starts_with(VARIADIC_MARKER))
+            .map(|parameter| parameter.name.clone())
+            .collect()
+    };
+    let from = positional(old);
+    let to = positional(new);
+    let moved = from.iter()
+        .enumerate()
+        .filter(|(_, name)| new.iter().any(|parameter| &parameter.name == *name))
+        .any(|(position, name)| to.get(position) != Some(name));
+
+    match moved {
+        true => Some(ApiChange::ParametersReordered { method: method.to_string(), from, to }),
+        false => None
+    }
+}
+
+/// Compares the public API of two versions of a project, class by class
+///
+/// The public classes are the module level ones whose import path, found
+/// through the package re-exports, has no part starting with an underscore.
+/// The classes are paired by that path, so moving a class between modules
+/// while keeping its public import is not a change.
+///
+/// # Arguments
+///
+/// * `old` - The project before the change
+/// * `new` - The project after the change
+///
+/// # Output
+///
+/// The classes with changes, ordered by their import path
+pub fn diff_projects(old: &Project, new: &Project) -> Vec<ClassChanges> {
+    let old_classes = public_classes(old);
+    let new_classes = public_classes(new);
+    let mut reports: Vec<ClassChanges> = Vec::new();
+
+    for (class_name, old_id) in old_classes.iter() {
+        let changes = match new_classes.iter().find(|(new_name, _)| new_name == class_name) {
+            Some((_, new_id)) => diff_public_classes(&old.classes[*old_id].class, &new.classes[*new_id].class),
+            None => vec![ApiChange::ClassRemoved { class_name: class_name.clone() }]
+        };
+        if !changes.is_empty() {
+            reports.push(ClassChanges::new(class_name.clone(), changes));
+        }
+    }
+    for (class_name, _) in new_classes.iter() {
+        if !old_classes.iter().any(|(old_name, _)| old_name == class_name) {
+            reports.push(ClassChanges::new(class_name.clone(), vec![ApiChange::ClassAdded { class_name: class_name.clone() }]));
+        }
+    }
+    reports.sort_by(|first, second| first.class_name.cmp(&second.class_name));
+    reports
+}
+
+/// The public classes of a project by their dotted import path, each path once
+fn public_classes(project: &Project) -> Vec<(String, usize)> {
+    let mut classes: Vec<(String, usize)> = Vec::new();
+    for class_id in 0..project.classes.len() {
+        if is_nested(&project.classes[class_id]) || project.module_of(class_id).is_notebook() {
+            continue
+        }
+        let (module, name) = project.public_path(class_id);
+        let class_name = format!("{}.{}", module, name);
+        if class_name.split('.').all(is_public) && !classes.iter().any(|(found, _)| *found == class_name) {
+            classes.push((class_name, class_id));
+        }
+    }
+    classes
+}
+
+/// Builds the machine readable report of the API changes between two versions
+///
+/// # Arguments
+///
+/// * `versions` - The revisions or directories compared, old one first
+/// * `reports` - The changes of each class
+pub fn json_report(versions: (&str, &str), reports: &[ClassChanges]) -> Value {
+    let changes: Vec<Value> = reports.iter()
+        .flat_map(|report| report.changes.iter().map(move |change| json!({
+            "class": report.class_name,
+            "kind": change.kind(),
+            "breaking": change.is_breaking(),
+            "description": change.to_string()
+        })))
+        .collect();
+    let breaking = changes.iter().filter(|change| change["breaking"] == true).count();
+
+    json!({
+        "from": versions.0,
+        "to": versions.1,
+        "breaking": breaking,
+        "compatible": changes.len() - breaking,
+        "changes": changes
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::sources::SourceFile;
+
+    fn python_class(code: &str) -> PythonClass {
+        let lines: Vec<String> = code.lines().map(String::from).collect();
+        let inheritance = match lines[0].split_once('(') {
+            Some((_, bases)) => bases.trim_end_matches("):").split(", ").map(String::from).collect(),
+            None => Vec::new()
+        };
+        PythonClass::new(lines, String::from("Cart"), inheritance, String::from("None"))
+    }
+
+    #[test]
+    fn test_diff_classes() {
+        let old = python_class("class Cart(Base):
+    def add(self, item: Item, qty: int = 1) -> None:
+        pass
+    def total(self, currency) -> int:
+        pass
+    def clear(self):
+        pass
+");
+        let new = python_class("class Cart(Base, Mixin):
+    def add(self, product: Item, qty: float = 2, force: bool = False) -> None:
+        pass
+    def total(self, currency) -> Decimal:
+        pass
+    def discount(self, code: str):
+        pass
+");
+
+        assert_eq!(diff_classes(&old, &new), vec![
+            ApiChange::BasesChanged {
+                from: vec![String::from("Base")],
+                to: vec![String::from("Base"), String::from("Mixin")]
+            },
+            ApiChange::MethodRemoved { signature: String::from("clear(self) -> None") },
+            ApiChange::ParameterRenamed {
+                method: String::from("add"), from: String::from("item"), to: String::from("product")
+            },
+            ApiChange::TypeChanged {
+                method: String::from("add"), parameter: String::from("qty"), from: String::from("int"), to: String::from("float")
+            },
+            ApiChange::DefaultChanged {
+                method: String::from("add"), parameter: String::from("qty"), from: Some(String::from("1")), to: Some(String::from("2"))
+            },
+            ApiChange::ParameterAdded {
+                method: String::from("add"), parameter: String::from("force: bool = False"), required: false
+            },
+            ApiChange::ReturnTypeChanged {
+                method: String::from("total"), from: String::from("int"), to: String::from("Decimal")
+            },
+            ApiChange::MethodAdded { signature: String::from("discount(self, code: str) -> None") },
+        ]);
+    }
+
+    #[test]
+    fn test_diff_same_class() {
+        let code = "class Cart:\n    def total(self, currency: str = 'EUR') -> int:\n        pass\n";
+
+        assert!(diff_classes(&python_class(code), &python_class(code)).is_empty());
+    }
+
+    #[test]
+    fn test_parameter_api() {
+        let untyped = ParameterApi::new(&Parameter::new(String::from("retries=3"), String::from("None")));
+        let typed = ParameterApi::new(&Parameter::new(String::from("retries"), String::from("int = 3")));
+
+        assert_eq!(untyped.written(), "retries = 3");
+        assert_eq!(typed.written(), "retries: int = 3");
+    }
+
+    #[test]
+    fn test_is_public() {
+        assert!(is_public("total"));
+        assert!(is_public("__init__"));
+        assert!(is_public("**options"));
+        assert!(!is_public("_round"));
+        assert!(!is_public("__secret"));
+    }
+
+    #[test]
+    fn test_diff_public_classes() {
+        let old = python_class("class Cart:
+    def add(self, item, qty, price=None, _trace=False, *args):
+        pass
+    def _round(self, value):
+        pass
+");
+        let new = python_class("class Cart:
+    def add(self, qty, item, price, note=None, *args, currency):
+        pass
+");
+
+        let changes = diff_public_classes(&old, &new);
+        assert_eq!(changes, vec![
+            ApiChange::ParametersReordered {
+                method: String::from("add"),
+                from: vec![
+                    String::from("self"), String::from("item"), String::from("qty"), String::from("price"), String::from("_trace")
+                ],
+                to: vec![
+                    String::from("self"), String::from("qty"), String::from("item"), String::from("price"), String::from("note")
+                ]
+            },
+            ApiChange::DefaultChanged {
+                method: String::from("add"), parameter: String::from("price"), from: Some(String::from("None")), to: None
+            },
+            ApiChange::ParameterAdded { method: String::from("add"), parameter: String::from("note = None"), required: false },
+            ApiChange::ParameterAdded { method: String::from("add"), parameter: String::from("currency"), required: true },
+        ]);
+        assert_eq!(changes.iter().filter(|change| change.is_breaking()).count(), 3);
+    }
+
+    #[test]
+    fn test_shifted_positional_parameters() {
+        let old = python_class("class Cart:\n    def add(self, item, qty=1, *, note=None):\n        pass\n");
+        let inserted = python_class("class Cart:\n    def add(self, item, price=0, qty=1, *, note=None):\n        pass\n");
+        let private = python_class("class Cart:\n    def add(self, _trace, item, qty=1, *, note=None):\n        pass\n");
+        let appended = python_class("class Cart:\n    def add(self, item, qty=1, price=0, *, code=None, note=None):\n        pass\n");
+
+        assert_eq!(diff_public_classes(&old, &inserted)[0], ApiChange::ParametersReordered {
+            method: String::from("add"),
+            from: vec![String::from("self"), String::from("item"), String::from("qty")],
+            to: vec![String::from("self"), String::from("item"), String::from("price"), String::from("qty")]
+        });
+        assert!(diff_public_classes(&old, &private).iter().any(|change| change.kind() == "parameters_reordered"));
+        assert!(diff_public_classes(&old, &appended).iter().all(|change| !change.is_breaking()));
+    }
+
+    #[test]
+    fn test_diff_projects() {
+        let old = Project::new(Path::new("."), vec![
+            SourceFile::new(String::from("./shop/__init__.py"), String::from("from .cart import Cart\n")),
+            SourceFile::new(String::from("./shop/cart.py"), String::from(
+                "class Cart:\n    def total(self):\n        pass\n\nclass _Line:\n    pass\n\nclass Coupon:\n    pass\n"
+            )),
+        ]);
+        let new = Project::new(Path::new("."), vec![
+            SourceFile::new(String::from("./shop/__init__.py"), String::from("from ._cart import Cart\n")),
+            SourceFile::new(String::from("./shop/_cart.py"), String::from(
+                "class Cart:\n    def total(self):\n        pass\n\nclass Basket:\n    pass\n"
+            )),
+        ]);
+
+        let reports = diff_projects(&old, &new);
+        assert_eq!(reports, vec![
+            ClassChanges::new(String::from("shop.cart.Coupon"), vec![
+                ApiChange::ClassRemoved { class_name: String::from("shop.cart.Coupon") }
+            ]),
+        ]);
+
+        let report = json_report(("v1", "v2"), &reports);
+        assert_eq!(report["breaking"], 1);
+        assert_eq!(report["compatible"], 0);
+        assert_eq!(report["changes"][0]["kind"], "class_removed");
+        assert_eq!(report["changes"][0]["class"], "shop.cart.Coupon");
+    }
+}
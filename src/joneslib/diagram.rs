@@ -0,0 +1,492 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use super::{
+    objects::{Method, Parameter},
+    project::Project,
+    utils::extract_attributes
+};
+
+const DEFAULT_TYPE: &str = "None";
+const DIAGRAM_INDENT: &str = "    ";
+
+/// The languages a class diagram can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagramFormat {
+    Mermaid,
+    PlantUml,
+    Dot
+}
+impl FromStr for DiagramFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "mermaid" => Ok(DiagramFormat::Mermaid),
+            "plantuml" => Ok(DiagramFormat::PlantUml),
+            "dot" | "graphviz" => Ok(DiagramFormat::Dot),
+            _ => Err(format!("Unknown diagram format `{}`, expected mermaid, plantuml or dot", format))
+        }
+    }
+}
+
+/// An attribute or a method drawn inside a class box
+#[derive(Debug)]
+struct Member {
+    name: String,
+    signature: String,
+    static_type: Option<String>
+}
+
+/// A class drawn in the diagram with its members and the bases it points to
+#[derive(Debug)]
+struct DiagramClass {
+    id: String,
+    name: String,
+    attributes: Vec<Member>,
+    methods: Vec<Member>,
+    bases: Vec<DiagramBase>
+}
+
+/// A base an inheritance edge points to, with the written name of the bases
+/// defined outside of the project whose node id had to be sanitised
+#[derive(Debug)]
+struct DiagramBase {
+    id: String,
+    label: Option<String>
+}
+
+/// Picks the classes drawn for a class or a package
+///
+/// A class is drawn together with its ancestors defined in the project,
+/// while a module or package name selects every class defined inside it.
+///
+/// # Output
+///
+/// * `None` - if the name matches no class and no module of the project
+/// * `Some` - the selected classes, each one once
+pub fn select_classes(project: &Project, name: &str) -> Option<Vec<usize>> {
+    if let Some(class_id) = project.find_class(name) {
+        let mut selected = vec![class_id];
+        let mut position: usize = 0;
+        while position < selected.len() {
+            let current = selected[position];
+            for base in project.bases(current) {
+                match project.resolve_base(current, base) {
+                    Some(base_id) if !selected.contains(&base_id) => selected.push(base_id),
+                    _ => continue
+                }
+            }
+            position += 1;
+        }
+        return Some(selected)
+    }
+
+    let package_prefix = format!("{}.", name);
+    let selected: Vec<usize> = (0..project.classes.len())
+        .filter(|class_id| {
+            let module_name = &project.module_of(*class_id).name;
+            module_name == name || module_name.starts_with(&package_prefix)
+        })
+        .collect();
+
+    match selected.is_empty() {
+        true => None,
+        false => Some(selected)
+    }
+}
+
+/// Renders a class diagram with the attributes, the method signatures and
+/// the inheritance edges of the selected classes
+///
+/// # Arguments
+///
+/// * `project` - The project the classes were selected from
+/// * `class_ids` - The classes to be drawn
+/// * `format` - The diagram language
+pub fn render(project: &Project, class_ids: &[usize], format: DiagramFormat) -> String {
+    let node_ids = node_ids(project, class_ids);
+    let classes: Vec<DiagramClass> = class_ids.iter()
+        .map(|class_id| diagram_class(project, *class_id, &node_ids))
+        .collect();
+
+    match format {
+        DiagramFormat::Mermaid => render_mermaid(&classes),
+        DiagramFormat::PlantUml => render_plantuml(&classes),
+        DiagramFormat::Dot => render_dot(&classes)
+    }
+}
+
+/// The node identifier of every class drawn, either selected or pointed to
+/// by an edge
+///
+/// A class is identified by its name, unless another drawn class has the
+/// same name, in which case its module is added to tell the two apart.
+fn node_ids(project: &Project, class_ids: &[usize]) -> HashMap<usize, String> {
+    let mut drawn: Vec<usize> = class_ids.to_vec();
+    for class_id in class_ids.iter() {
+        for base in project.bases(*class_id) {
+            match project.resolve_base(*class_id, base) {
+                Some(base_id) if !drawn.contains(&base_id) => drawn.push(base_id),
+                _ => continue
+            }
+        }
+    }
+
+    drawn.iter()
+        .map(|class_id| {
+            let name = &project.classes[*class_id].class.name;
+            let shared = drawn.iter().any(|other| other != class_id && &project.classes[*other].class.name == name);
+            let node_id = match shared {
+                true => format!("{}.{}", project.module_of(*class_id).name, name).replace('.', "_"),
+                false => name.clone()
+            };
+            (*class_id, node_id)
+        })
+        .collect()
+}
+
+fn diagram_class(project: &Project, class_id: usize, node_ids: &HashMap<usize, String>) -> DiagramClass {
+    let indexed = &project.classes[class_id];
+    let bases = project.bases(class_id).iter()
+        .map(|base| match project.resolve_base(class_id, base).and_then(|base_id| node_ids.get(&base_id)) {
+            Some(node_id) => DiagramBase { id: node_id.clone(), label: None },
+            None => external_base(base)
+        })
+        .collect();
+
+    DiagramClass {
+        id: node_ids[&class_id].clone(),
+        name: indexed.class.name.clone(),
+        attributes: extract_attributes(&indexed.code).iter().map(attribute_member).collect(),
+        methods: indexed.class.methods.iter().map(method_member).collect(),
+        bases
+    }
+}
+
+/// A base defined outside of the project, e.g. `models.Model`, whose dots
+/// and other characters not allowed in a node id are replaced
+fn external_base(written: &str) -> DiagramBase {
+    let name = written.split('[').next().unwrap_or(written).trim();
+    let id = name.replace(|chr: char| !chr.is_alphanumeric() && chr != '_', "_");
+    let label = match id == name {
+        true => None,
+        false => Some(name.to_string())
+    };
+    DiagramBase { id, label }
+}
+
+/// The external bases drawn with a label, each one once
+fn labelled_bases(classes: &[DiagramClass]) -> Vec<(&str, &str)> {
+    let mut labelled: Vec<(&str, &str)> = Vec::new();
+    for base in classes.iter().flat_map(|class| class.bases.iter()) {
+        if let Some(label) = &base.label {
+            if !labelled.iter().any(|(id, _)| *id == base.id) {
+                labelled.push((&base.id, label));
+            }
+        }
+    }
+    labelled
+}
+
+fn attribute_member(attribute: &Parameter) -> Member {
+    Member {
+        name: attribute.name.clone(),
+        signature: attribute.name.clone(),
+        static_type: static_type(&attribute.static_type)
+    }
+}
+
+/// The method signature without `self`, `cls` and the default values
+fn method_member(method: &Method) -> Member {
+    let parameters: Vec<String> = method.parameters.iter()
+        .enumerate()
+        .filter(|(position, parameter)| !(*position == 0 && (parameter.name == "self" || parameter.name == "cls")))
+        .map(|(_, parameter)| match static_type(&parameter.static_type) {
+            Some(static_type) => format!("{}: {}", parameter.name, static_type),
+            None => parameter.name.clone()
+        })
+        .collect();
+
+    Member {
+        name: method.name.clone(),
+        signature: format!("{}({})", method.name, parameters.join(", ")),
+        static_type: static_type(&method.output)
+    }
+}
+
+/// The written type without the default value, if the member has one
+fn static_type(written: &str) -> Option<String> {
+    let static_type = written.split('=').next().unwrap_or(written).trim();
+    match static_type.is_empty() || static_type == DEFAULT_TYPE {
+        true => None,
+        false => Some(static_type.to_string())
+    }
+}
+
+/// UML visibility of a Python member, based on the underscore convention
+fn visibility(name: &str) -> char {
+    match name.starts_with('_') && !name.ends_with("__") {
+        true => '-',
+        false => '+'
+    }
+}
+
+fn render_mermaid(classes: &[DiagramClass]) -> String {
+    // Mermaid writes generics between tildes, e.g. List~int~
+    let generics = |text: &str| text.replace(['[', ']'], "~");
+    let mut lines: Vec<String> = vec![String::from("classDiagram")];
+
+    for class in classes.iter() {
+        match class.id == class.name {
+            true => lines.push(format!("{}class {} {{", DIAGRAM_INDENT, class.id)),
+            false => lines.push(format!("{}class {}[\"{}\"] {{", DIAGRAM_INDENT, class.id, class.name))
+        }
+        for attribute in class.attributes.iter() {
+            let member = match &attribute.static_type {
+                Some(static_type) => format!("{} {}", generics(static_type), attribute.signature),
+                None => attribute.signature.clone()
+            };
+            lines.push(format!("{0}{0}{1}{2}", DIAGRAM_INDENT, visibility(&attribute.name), member));
+        }
+        for method in class.methods.iter() {
+            let output = match &method.static_type {
+                Some(output) => format!(" {}", generics(output)),
+                None => String::new()
+            };
+            lines.push(format!(
+                "{0}{0}{1}{2}{3}", DIAGRAM_INDENT, visibility(&method.name), generics(&method.signature), output
+            ));
+        }
+        lines.push(format!("{}}}", DIAGRAM_INDENT));
+    }
+    for (id, label) in labelled_bases(classes) {
+        lines.push(format!("{}class {}[\"{}\"]", DIAGRAM_INDENT, id, label));
+    }
+    for class in classes.iter() {
+        for base in class.bases.iter() {
+            lines.push(format!("{}{} <|-- {}", DIAGRAM_INDENT, base.id, class.id));
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_plantuml(classes: &[DiagramClass]) -> String {
+    let mut lines: Vec<String> = vec![String::from("@startuml")];
+
+    for class in classes.iter() {
+        match class.id == class.name {
+            true => lines.push(format!("class {} {{", class.id)),
+            false => lines.push(format!("class \"{}\" as {} {{", class.name, class.id))
+        }
+        for member in class.attributes.iter().chain(class.methods.iter()) {
+            let static_type = match &member.static_type {
+                Some(static_type) => format!(" : {}", static_type),
+                None => String::new()
+            };
+            lines.push(format!("{}{}{}{}", DIAGRAM_INDENT, visibility(&member.name), member.signature, static_type));
+        }
+        lines.push(String::from("}"));
+    }
+    for (id, label) in labelled_bases(classes) {
+        lines.push(format!("class \"{}\" as {}", label, id));
+    }
+    for class in classes.iter() {
+        for base in class.bases.iter() {
+            lines.push(format!("{} <|-- {}", base.id, class.id));
+        }
+    }
+    lines.push(String::from("@enduml"));
+    lines.join("\n")
+}
+
+fn render_dot(classes: &[DiagramClass]) -> String {
+    let mut lines: Vec<String> = vec![
+        String::from("digraph classes {"),
+        format!("{}rankdir=BT;", DIAGRAM_INDENT),
+        format!("{}node [shape=record];", DIAGRAM_INDENT)
+    ];
+
+    for class in classes.iter() {
+        lines.push(format!(
+            "{}\"{}\" [label=\"{{{}|{}|{}}}\"];",
+            DIAGRAM_INDENT,
+            class.id,
+            escape_record(&class.name),
+            dot_members(&class.attributes),
+            dot_members(&class.methods)
+        ));
+    }
+    for (id, label) in labelled_bases(classes) {
+        lines.push(format!("{}\"{}\" [label=\"{}\"];", DIAGRAM_INDENT, id, escape_record(label)));
+    }
+    for class in classes.iter() {
+        for base in class.bases.iter() {
+            lines.push(format!("{}\"{}\" -> \"{}\" [arrowhead=empty];", DIAGRAM_INDENT, class.id, base.id));
+        }
+    }
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+/// Left aligned lines of a DOT record field
+fn dot_members(members: &[Member]) -> String {
+    members.iter()
+        .map(|member| {
+            let text = match &member.static_type {
+                Some(static_type) => format!("{}{}: {}", visibility(&member.name), member.signature, static_type),
+                None => format!("{}{}", visibility(&member.name), member.signature)
+            };
+            format!("{}\\l", escape_record(&text))
+        })
+        .collect()
+}
+
+/// Escapes the characters with a special meaning inside DOT record labels
+fn escape_record(text: &str) -> String {
+    let mut escaped = String::new();
+    for chr in text.chars() {
+        if matches!(chr, '{' | '}' | '|' | '<' | '>' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(chr);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::sources::SourceFile;
+
+    fn billing_project() -> Project {
+        let sources = vec![
+            SourceFile::new(String::from("./billing/base.py"), String::from(
+"class BaseService(ABC):
+    def close(self) -> None:
+        pass
+"
+            )),
+            SourceFile::new(String::from("./billing/invoices.py"), String::from(
+"from .base import BaseService as BS
+
+class InvoiceService(BS):
+    retries: int = 3
+
+    def __init__(self, repo: Repo):
+        self._repo = repo
+
+    def create(self, lines: List[Line], total: int = 0) -> Invoice:
+        pass
+"
+            )),
+        ];
+        Project::new(Path::new("."), sources)
+    }
+
+    #[test]
+    fn test_select_classes() {
+        let project = billing_project();
+        let service = project.find_class("InvoiceService").unwrap();
+        let base = project.find_class("BaseService").unwrap();
+
+        assert_eq!(select_classes(&project, "InvoiceService"), Some(vec![service, base]));
+        assert_eq!(select_classes(&project, "billing"), Some(vec![base, service]));
+        assert_eq!(select_classes(&project, "shipping"), None);
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let project = billing_project();
+        let class_ids = vec![project.find_class("InvoiceService").unwrap()];
+        let expected = "classDiagram
+    class InvoiceService {
+        +int retries
+        -_repo
+        +__init__(repo: Repo)
+        +create(lines: List~Line~, total: int) Invoice
+    }
+    BaseService <|-- InvoiceService";
+
+        assert_eq!(render(&project, &class_ids, DiagramFormat::Mermaid), expected);
+    }
+
+    #[test]
+    fn test_render_plantuml_and_dot() {
+        let project = billing_project();
+        let class_ids = select_classes(&project, "InvoiceService").unwrap();
+
+        let plantuml = render(&project, &class_ids, DiagramFormat::PlantUml);
+        assert!(plantuml.starts_with("@startuml\nclass InvoiceService {\n    +retries : int\n"));
+        assert!(plantuml.contains("class BaseService {\n    +close()\n}"));
+        assert!(plantuml.ends_with("ABC <|-- BaseService\n@enduml"));
+
+        let dot = render(&project, &class_ids, DiagramFormat::Dot);
+        assert!(dot.contains("\"BaseService\" [label=\"{BaseService||+close()\\l}\"];"));
+        assert!(dot.contains("\"InvoiceService\" -> \"BaseService\" [arrowhead=empty];"));
+    }
+
+    #[test]
+    fn test_render_same_named_classes() {
+        let sources = vec![
+            SourceFile::new(String::from("./shop/orders.py"), String::from("class Item:\n    pass\n")),
+            SourceFile::new(String::from("./shop/stock.py"), String::from(
+                "from .orders import Item as OrderItem\n\nclass Item(OrderItem):\n    pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        let class_ids = select_classes(&project, "shop").unwrap();
+
+        let mermaid = render(&project, &class_ids, DiagramFormat::Mermaid);
+        assert!(mermaid.contains("    class shop_orders_Item[\"Item\"] {"));
+        assert!(mermaid.contains("    class shop_stock_Item[\"Item\"] {"));
+        assert!(mermaid.ends_with("    shop_orders_Item <|-- shop_stock_Item"));
+
+        let plantuml = render(&project, &class_ids, DiagramFormat::PlantUml);
+        assert!(plantuml.contains("class \"Item\" as shop_stock_Item {"));
+
+        let dot = render(&project, &class_ids, DiagramFormat::Dot);
+        assert!(dot.contains("\"shop_stock_Item\" -> \"shop_orders_Item\" [arrowhead=empty];"));
+    }
+
+    #[test]
+    fn test_render_external_dotted_base() {
+        let sources = vec![
+            SourceFile::new(String::from("./shop/models.py"), String::from(
+                "from django.db import models\n\nclass Order(models.Model):\n    pass\n\nclass Item(models.Model, Generic[T]):\n    pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        let class_ids = select_classes(&project, "shop").unwrap();
+
+        let mermaid = render(&project, &class_ids, DiagramFormat::Mermaid);
+        assert_eq!(mermaid.matches("    class models_Model[\"models.Model\"]\n").count(), 1);
+        assert!(mermaid.contains("    models_Model <|-- Order\n"));
+        assert!(mermaid.ends_with("    Generic <|-- Item"));
+        assert!(!mermaid.contains("models.Model <|--"));
+
+        let plantuml = render(&project, &class_ids, DiagramFormat::PlantUml);
+        assert!(plantuml.contains("class \"models.Model\" as models_Model\n"));
+        assert!(plantuml.contains("models_Model <|-- Item\n"));
+
+        let dot = render(&project, &class_ids, DiagramFormat::Dot);
+        assert!(dot.contains("    \"models_Model\" [label=\"models.Model\"];\n"));
+        assert!(dot.contains("    \"Order\" -> \"models_Model\" [arrowhead=empty];\n"));
+    }
+
+    #[test]
+    fn test_diagram_format() {
+        assert_eq!("PlantUML".parse::<DiagramFormat>(), Ok(DiagramFormat::PlantUml));
+        assert!("svg".parse::<DiagramFormat>().is_err());
+    }
+}
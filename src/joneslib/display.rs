@@ -7,22 +7,378 @@ License: MIT
 
 Copyright 2021 Vlad Nedelcu
 */
-use super::objects;
-use super::ClassMatch;
+use super::objects::{ClassMatch, RankedClass, MethodMatch, TypeMatch, DocMatch, IndexedClass, Ancestor, Subclass, Usage, IndexedFunction, Constant, ApiChange, ClassChanges, MethodHistory};
+use super::matcher::Spans;
+use super::hierarchy::{MethodGroup, MroEntry};
+use super::project::Project;
+use super::calls::{CallGraph, CallNode, MethodId, method_name};
+use std::path::Path;
 use ansi_term::Colour;
 
-pub fn output_class(python_class: &objects::PythonClass) {
-    println!("{}", python_class);
+/// Prints the class with its own methods or, when the inherited view is
+/// requested, with the methods grouped by the class defining them
+///
+/// # Arguments
+///
+/// * `indexed` - The class to be printed
+/// * `import_path` - The ready to paste import of the class, if known
+/// * `root` - The search root the class was found in, when searching several
+/// * `inherited` - The project and the method groups of the inherited view
+pub fn output_class(
+    indexed: &IndexedClass, import_path: Option<&str>, root: Option<&Path>, inherited: Option<(&Project, &[MethodGroup])>
+) {
+    println!("{}", indexed.class);
+    if let Some(root) = root {
+        println!("* root -> {}", Colour::Purple.paint(root.to_string_lossy()));
+    }
+    if let Some(import_path) = import_path {
+        println!("* import -> {}", Colour::Green.paint(import_path));
+    }
+    if let Some(stub) = &indexed.stub {
+        let docstring_source = match stub.docstring {
+            true => "stub",
+            false => "implementation"
+        };
+        println!(
+            "* stub -> {} (signatures from the stub, docstring from the {})",
+            Colour::Purple.paint(&stub.location),
+            docstring_source
+        );
+    }
+    println!("\n# Methods\n-------");
 
-    for method in python_class.methods.iter() {
-        println!("{}", method);
-        for parameter in method.parameters.iter() {
+    let (project, groups) = match inherited {
+        Some(inherited) => inherited,
+        None => {
+            for method in indexed.class.methods.iter() {
+                println!("{}{}", method, stub_marker(indexed, &method.name));
+                for parameter in method.parameters.iter() {
+                    println!("{}", parameter);
+                }
+            }
+            return
+        }
+    };
+
+    let resolution_order: Vec<String> = groups.iter().map(|group| group.entry.name(project)).collect();
+    println!("# MRO :: {}", Colour::Cyan.paint(resolution_order.join(" -> ")));
+    for group in groups.iter() {
+        let location = match &group.entry {
+            MroEntry::Class(class_id) => Colour::Purple.paint(project.classes[*class_id].location()),
+            MroEntry::External(_) => Colour::Red.paint("[external]")
+        };
+        println!("## [{}] -> {}", Colour::Green.paint(group.entry.name(project)), location);
+        let marker = |method_name: &str| match &group.entry {
+            MroEntry::Class(class_id) => stub_marker(&project.classes[*class_id], method_name),
+            MroEntry::External(_) => String::new()
+        };
+        for inherited in group.methods.iter() {
+            match &inherited.overrides {
+                Some(overridden) => println!(
+                    "{}{} {}",
+                    inherited.method,
+                    marker(&inherited.method.name),
+                    Colour::Red.paint(format!("(overrides {})", overridden))
+                ),
+                None => println!("{}{}", inherited.method, marker(&inherited.method.name))
+            }
+            for parameter in inherited.method.parameters.iter() {
+                println!("{}", parameter);
+            }
+        }
+    }
+}
+
+pub fn functions(functions: &[&IndexedFunction], constants: &[&Constant]) {
+    for indexed in functions.iter() {
+        println!(
+            "# Function :: [{}] -> {}",
+            Colour::Cyan.paint(&indexed.function.name),
+            Colour::Purple.paint(indexed.location())
+        );
+        println!("{}", Colour::Yellow.paint(indexed.docstring.as_deref().unwrap_or("None")));
+        println!("* decorate -> {}", Colour::Green.paint(indexed.decorators.join(", ")));
+        println!("{}", indexed.function);
+        for parameter in indexed.function.parameters.iter() {
             println!("{}", parameter);
         }
+        println!();
+    }
+    for constant in constants.iter() {
+        println!(
+            "# Constant :: [{}] -> {}",
+            Colour::Cyan.paint(&constant.constant.name),
+            Colour::Purple.paint(constant.location())
+        );
+        println!("* type -> {}", Colour::Green.paint(&constant.constant.static_type));
+        println!("* value -> {}", Colour::Yellow.paint(&constant.value));
+        println!();
     }
 }
 
-pub fn not_found_message() -> () {
+pub fn module_overview(project: &Project, module_id: usize) {
+    let module = &project.modules[module_id];
+    let exports = match &module.exports {
+        Some(exports) => exports.join(", "),
+        None => String::from("None")
+    };
+    println!(
+        "# Module :: [{}] -> {}",
+        Colour::Cyan.paint(&module.name),
+        Colour::Purple.paint(&module.file_path)
+    );
+    println!("* all -> {}", Colour::Green.paint(exports));
+
+    println!("## Imports");
+    for import in module.imports.iter() {
+        println!(
+            ":: {} -> {}",
+            Colour::Yellow.paint(import.statement()),
+            Colour::Purple.paint(module.location(import.line))
+        );
+    }
+    println!("## Classes");
+    for indexed in project.classes_of(module_id) {
+        let summary = match indexed.class.docstring.as_str() {
+            "None" => None,
+            docstring => docstring.lines().map(|line| line.trim()).find(|line| !line.is_empty())
+        };
+        println!(
+            ":: {}{} -> {}",
+            Colour::Yellow.paint(indexed.header.trim_end_matches(':')),
+            summary.map(|summary| format!(" {}", summary)).unwrap_or_default(),
+            Colour::Purple.paint(indexed.location())
+        );
+    }
+    println!("## Functions");
+    for indexed in module.functions.iter() {
+        println!(
+            ":: {} -> {}",
+            Colour::Yellow.paint(indexed.function.signature()),
+            Colour::Purple.paint(indexed.location())
+        );
+    }
+    println!("## Constants");
+    for constant in module.constants.iter() {
+        println!(
+            ":: {}: {} = {} -> {}",
+            Colour::Yellow.paint(&constant.constant.name),
+            Colour::Green.paint(&constant.constant.static_type),
+            constant.value,
+            Colour::Purple.paint(constant.location())
+        );
+    }
+
+    let submodules = project.submodules(module_id);
+    if !submodules.is_empty() {
+        println!("## Submodules");
+        for submodule in submodules.iter() {
+            println!(
+                ":: {} -> {}",
+                Colour::Yellow.paint(&submodule.name),
+                Colour::Purple.paint(&submodule.file_path)
+            );
+        }
+    }
+}
+
+/// Marks the methods whose signature was taken from a `.pyi` stub
+fn stub_marker(indexed: &IndexedClass, method_name: &str) -> String {
+    match indexed.is_stub_method(method_name) {
+        true => format!(" {}", Colour::Blue.paint("[stub]")),
+        false => String::new()
+    }
+}
+
+pub fn ancestors(indexed: &IndexedClass, ancestors: &[Ancestor]) {
+    println!(
+        "# Ancestors :: [{}] -> {}",
+        Colour::Cyan.paint(&indexed.class.name),
+        Colour::Purple.paint(indexed.location())
+    );
+    print_ancestors(ancestors, 0);
+}
+
+fn print_ancestors(ancestors: &[Ancestor], depth: usize) {
+    for ancestor in ancestors.iter() {
+        let name = match ancestor.written == ancestor.class_name {
+            true => ancestor.class_name.clone(),
+            false => format!("{} ({})", ancestor.written, ancestor.class_name)
+        };
+        let location = match ancestor.is_external() {
+            true => Colour::Red.paint("[external]"),
+            false => Colour::Purple.paint(ancestor.location.clone().unwrap_or_default())
+        };
+        println!("{}* {} -> {}", "  ".repeat(depth), Colour::Green.paint(name), location);
+        print_ancestors(&ancestor.parents, depth + 1);
+    }
+}
+
+pub fn subclasses(indexed: &IndexedClass, subclasses: &[Subclass]) {
+    println!(
+        "# Subclasses :: [{}] -> {}",
+        Colour::Cyan.paint(&indexed.class.name),
+        Colour::Purple.paint(indexed.location())
+    );
+    print_subclasses(subclasses, 0);
+}
+
+fn print_subclasses(subclasses: &[Subclass], depth: usize) {
+    for subclass in subclasses.iter() {
+        println!(
+            "{}* {} -> {}",
+            "  ".repeat(depth),
+            Colour::Green.paint(&subclass.class_name),
+            Colour::Purple.paint(&subclass.location)
+        );
+        print_subclasses(&subclass.children, depth + 1);
+    }
+}
+
+pub fn usages(indexed: &IndexedClass, usages: &[Usage]) {
+    println!(
+        "# Usages :: [{}] -> {}",
+        Colour::Cyan.paint(&indexed.class.name),
+        Colour::Purple.paint(indexed.location())
+    );
+    for (counter, usage) in usages.iter().enumerate() {
+        if counter == 0 || usages[counter - 1].kind != usage.kind {
+            let total = usages.iter().filter(|other| other.kind == usage.kind).count();
+            println!("## {} ({})", Colour::Green.paint(usage.kind.to_string()), total);
+        }
+        println!(
+            ":: {} -> {}",
+            Colour::Yellow.paint(&usage.code),
+            Colour::Purple.paint(&usage.location)
+        );
+    }
+}
+
+/// Prints the API changes of a class between two versions of it
+///
+/// # Arguments
+///
+/// * `class_name` - The compared class
+/// * `versions` - The revisions or directories compared, old one first
+/// * `changes` - The changes from the old version to the new one
+pub fn api_changes(class_name: &str, versions: (&str, &str), changes: &[ApiChange]) {
+    println!(
+        "# Diff :: [{}] {} -> {}",
+        Colour::Cyan.paint(class_name),
+        Colour::Purple.paint(versions.0),
+        Colour::Purple.paint(versions.1)
+    );
+    if changes.is_empty() {
+        return println!("{}", Colour::Green.paint("No API changes"))
+    }
+    for change in changes.iter() {
+        let colour = match change.marker() {
+            '+' => Colour::Green,
+            '-' => Colour::Red,
+            _ => Colour::Yellow
+        };
+        println!("{} {}", colour.paint(change.marker().to_string()), change);
+    }
+}
+
+/// Prints the changes of the public API between two versions of a project,
+/// marking the ones breaking the callers
+///
+/// # Arguments
+///
+/// * `versions` - The revisions or directories compared, old one first
+/// * `reports` - The changes of each class
+pub fn breaking_changes(versions: (&str, &str), reports: &[ClassChanges]) {
+    println!("# Breaking :: {} -> {}", Colour::Purple.paint(versions.0), Colour::Purple.paint(versions.1));
+    if reports.is_empty() {
+        return println!("{}", Colour::Green.paint("No API changes"))
+    }
+    for report in reports.iter() {
+        println!("[{}]", Colour::Cyan.paint(&report.class_name));
+        for change in report.changes.iter() {
+            match change.is_breaking() {
+                true => println!("  {} {} {}", Colour::Red.paint(change.marker().to_string()), change, Colour::Red.bold().paint("[breaking]")),
+                false => println!("  {} {}", Colour::Green.paint(change.marker().to_string()), change)
+            }
+        }
+    }
+    let changes: Vec<&ApiChange> = reports.iter().flat_map(|report| report.changes.iter()).collect();
+    let breaking = changes.iter().filter(|change| change.is_breaking()).count();
+    let summary = format!("{} breaking, {} compatible", breaking, changes.len() - breaking);
+    match breaking {
+        0 => println!("{}", Colour::Green.paint(summary)),
+        _ => println!("{}", Colour::Red.paint(summary))
+    }
+}
+
+/// Prints when each method of a class was introduced and when its signature last changed
+///
+/// # Arguments
+///
+/// * `indexed` - The class whose history is printed
+/// * `history` - The history of each method of the class
+pub fn method_history(indexed: &IndexedClass, history: &[MethodHistory]) {
+    println!("# History :: [{}] {}", Colour::Cyan.paint(&indexed.class.name), indexed.location());
+    if history.is_empty() {
+        return println!("{}", Colour::Yellow.paint("No methods"))
+    }
+    let uncommitted = Colour::Yellow.paint("uncommitted");
+    for method in history.iter() {
+        println!("\n:: [{}] {}", Colour::Cyan.paint(&method.name), method.signature);
+        match &method.introduced {
+            Some(commit) => println!("  * introduced -> {}", Colour::Green.paint(commit.to_string())),
+            None => println!("  * introduced -> {}", uncommitted)
+        }
+        if let Some(commit) = &method.changed {
+            println!("  * signature changed -> {}", Colour::Purple.paint(commit.to_string()));
+        }
+        // Edited in the working directory after its last committed change
+        if method.uncommitted && method.introduced.is_some() {
+            println!("  * signature edited -> {}", uncommitted);
+        }
+    }
+}
+
+pub fn calls(project: &Project, graph: &CallGraph, methods: &[MethodId]) {
+    for method in methods.iter() {
+        println!(
+            "# Calls :: [{}] -> {}",
+            Colour::Cyan.paint(method_name(project, method)),
+            Colour::Purple.paint(project.classes[method.0].method_location(&method.1))
+        );
+        print_calls(&graph.call_tree(project, method), 0);
+
+        let callers = graph.callers(method);
+        if !callers.is_empty() {
+            println!("## Called by");
+            for (caller, call) in callers.iter() {
+                println!(
+                    "* {} -> {}",
+                    Colour::Green.paint(method_name(project, caller)),
+                    Colour::Purple.paint(&call.location)
+                );
+            }
+        }
+        println!();
+    }
+}
+
+fn print_calls(nodes: &[CallNode], depth: usize) {
+    for node in nodes.iter() {
+        let name = match node.name == node.written {
+            true => format!("{} {}", node.written, Colour::Red.paint("[unresolved]")),
+            false => format!("{} ({})", Colour::Green.paint(&node.name), node.written)
+        };
+        let repeated = match node.repeated {
+            true => " ...",
+            false => ""
+        };
+        println!("{}* {}{} -> {}", "  ".repeat(depth), name, repeated, Colour::Purple.paint(&node.location));
+        print_calls(&node.children, depth + 1);
+    }
+}
+
+pub fn not_found_message() {
     println!(
         "{}: {}",
         Colour::Green.paint("Output"),
@@ -30,13 +386,107 @@ pub fn not_found_message() -> () {
     )
 }
 
-pub fn class_matches(found_match_classes: Vec<ClassMatch>) -> () {
+pub fn error_message(message: &str) {
+    println!(
+        "{}: {}",
+        Colour::Red.paint("Error"),
+        Colour::Yellow.paint(message)
+    )
+}
+
+pub fn class_matches(found_match_classes: Vec<ClassMatch>) {
     println!("> [{}]", Colour::Cyan.paint("FOUND MATCHES"));
-    for line in found_match_classes.iter() {
+    for class_match in found_match_classes.iter() {
+        println!(
+            ":: {} -> {}",
+            highlight(class_match.header.trim_end_matches('\r'), &class_match.spans),
+            Colour::Purple.paint(&class_match.file_path)
+        )
+    }
+}
+
+pub fn queried_classes(found_classes: Vec<IndexedClass>) {
+    println!("> [{}]", Colour::Cyan.paint("FOUND CLASSES"));
+    for indexed in found_classes.iter() {
         println!(
             ":: {} -> {}",
-            Colour::Yellow.paint(&line.0.replace("\r", "")),
-            Colour::Purple.paint(&line.1)
+            Colour::Yellow.paint(&indexed.header),
+            Colour::Purple.paint(indexed.location())
         )
     }
-}
\ No newline at end of file
+}
+
+pub fn ranked_classes(ranked_classes: Vec<RankedClass>) {
+    println!("> [{}]", Colour::Cyan.paint("RANKED MATCHES"));
+    for ranked in ranked_classes.iter() {
+        println!(
+            ":: [{}] {} -> {}",
+            Colour::Green.paint(format!("{:>3}", ranked.score)),
+            Colour::Yellow.paint(&ranked.indexed.header),
+            Colour::Purple.paint(ranked.indexed.location())
+        );
+        println!("   * matched on: {}", Colour::Cyan.paint(ranked.hits.join("; ")));
+    }
+}
+
+pub fn method_matches(found_methods: Vec<MethodMatch>) {
+    println!("> [{}]", Colour::Cyan.paint("FOUND METHODS"));
+    for method_match in found_methods.iter() {
+        println!(
+            ":: {}.{} -> {}",
+            Colour::Cyan.paint(&method_match.class_name),
+            highlight(&method_match.method.signature(), &method_match.spans),
+            Colour::Purple.paint(&method_match.location)
+        )
+    }
+}
+
+pub fn type_matches(found_types: Vec<TypeMatch>) {
+    println!("> [{}]", Colour::Cyan.paint("FOUND TYPE USAGES"));
+    for type_match in found_types.iter() {
+        let kind = match type_match.parameter {
+            Some(_) => "param",
+            None => "return"
+        };
+        println!(
+            ":: [{}] {} -> {}",
+            Colour::Green.paint(kind),
+            Colour::Yellow.paint(type_match.usage()),
+            Colour::Purple.paint(&type_match.location)
+        )
+    }
+}
+
+pub fn doc_matches(found_docs: Vec<DocMatch>) {
+    println!("> [{}]", Colour::Cyan.paint("FOUND DOCSTRINGS"));
+    for doc_match in found_docs.iter() {
+        let owner = match &doc_match.method_name {
+            Some(method_name) => format!("{}.{}", doc_match.class_name, method_name),
+            None => doc_match.class_name.clone()
+        };
+        println!(
+            ":: {} -> {}",
+            Colour::Cyan.paint(owner),
+            Colour::Purple.paint(&doc_match.location)
+        );
+        println!("   {}", highlight(&doc_match.excerpt, &doc_match.spans));
+    }
+}
+
+/// Paints the text in yellow and the matched spans in bold red
+fn highlight(text: &str, spans: &Spans) -> String {
+    let mut highlighted = String::new();
+    let mut position: usize = 0;
+
+    for (start, end) in spans.iter() {
+        if *start < position || *end > text.len() {
+            continue
+        }
+        highlighted.push_str(&Colour::Yellow.paint(&text[position..*start]).to_string());
+        highlighted.push_str(&Colour::Red.bold().paint(&text[*start..*end]).to_string());
+        position = *end;
+    }
+    highlighted.push_str(&Colour::Yellow.paint(&text[position..]).to_string());
+
+    highlighted
+}
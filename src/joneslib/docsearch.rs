@@ -0,0 +1,214 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use super::{
+    docstrings::extract_function_docstring,
+    matcher::Spans,
+    objects::{DocMatch, IndexedClass}
+};
+
+const PHRASE_QUOTE: char = '"';
+const EXCERPT_BEFORE: usize = 40;
+const EXCERPT_LENGTH: usize = 120;
+const ELLIPSIS: &str = "...";
+const MISSING_DOCSTRING: &str = "None";
+
+/// Full text query matched against docstrings
+///
+/// Words written between double quotes are searched as a phrase, every
+/// other word is a separate term. A docstring matches the query only if it
+/// contains all the terms and phrases, regardless of the letter case.
+#[derive(Debug, PartialEq)]
+pub struct DocQuery {
+    terms: Vec<String>
+}
+impl DocQuery {
+    /// Splits the query into terms and phrases
+    ///
+    /// # Example
+    /// `refund "credit card"` is split into `refund` and `credit card`
+    pub fn parse(query: &str) -> Self {
+        let mut terms: Vec<String> = Vec::new();
+        for (counter, segment) in query.split(PHRASE_QUOTE).enumerate() {
+            let segment = segment.to_ascii_lowercase();
+            if counter % 2 == 1 {
+                terms.push(flatten(&segment));
+            } else {
+                terms.extend(segment.split_whitespace().map(|term| term.to_string()));
+            }
+        }
+        terms.retain(|term| !term.is_empty());
+
+        DocQuery {
+            terms
+        }
+    }
+
+    /// Looks for the query in a docstring
+    ///
+    /// # Output
+    ///
+    /// * `None` - if a term or phrase is missing from the docstring
+    /// * `Some` - the number of hits, the excerpt around the first hit and
+    ///   the spans of all the hits inside the excerpt
+    pub fn find(&self, docstring: &str) -> Option<(u32, String, Spans)> {
+        if self.terms.is_empty() {
+            return None
+        }
+        let text = flatten(docstring);
+        let lowered = text.to_ascii_lowercase();
+
+        let mut hits: Spans = Vec::new();
+        for term in self.terms.iter() {
+            let term_hits: Spans = lowered.match_indices(term.as_str())
+                .map(|(start, found)| (start, start + found.len()))
+                .collect();
+            if term_hits.is_empty() {
+                return None
+            }
+            hits.extend(term_hits);
+        }
+        hits.sort();
+
+        let (excerpt, spans) = excerpt(&text, &hits);
+        Some((hits.len() as u32, excerpt, spans))
+    }
+}
+
+/// Matches the query against the class docstring and every method docstring
+///
+/// # Arguments
+///
+/// * `indexed` - The class found in the project
+/// * `query` - The parsed full text query
+pub fn search_class(indexed: &IndexedClass, query: &DocQuery) -> Vec<DocMatch> {
+    let mut found_docs: Vec<DocMatch> = Vec::new();
+
+    if indexed.class.docstring != MISSING_DOCSTRING {
+        if let Some((score, excerpt, spans)) = query.find(&indexed.class.docstring) {
+            found_docs.push(DocMatch::new(
+                indexed.class.name.clone(), None, excerpt, spans, indexed.location(), score
+            ));
+        }
+    }
+
+    for method in indexed.class.methods.iter() {
+        let docstring = match extract_function_docstring(&indexed.method_code(&method.name)) {
+            Some(docstring) => docstring,
+            None => continue
+        };
+        if let Some((score, excerpt, spans)) = query.find(&docstring) {
+            found_docs.push(DocMatch::new(
+                indexed.class.name.clone(),
+                Some(method.name.clone()),
+                excerpt,
+                spans,
+                indexed.method_location(&method.name),
+                score
+            ));
+        }
+    }
+    found_docs
+}
+
+/// Collapses all the whitespace, newlines included, into single spaces
+pub fn flatten(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Cuts the text around the first hit and moves the hits inside the excerpt
+fn excerpt(text: &str, hits: &Spans) -> (String, Spans) {
+    let first_hit = hits.first().map(|hit| hit.0).unwrap_or(0);
+    let mut start = first_hit.saturating_sub(EXCERPT_BEFORE);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + EXCERPT_LENGTH).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let prefix = match start {
+        0 => "",
+        _ => ELLIPSIS
+    };
+    let suffix = match end == text.len() {
+        true => "",
+        false => ELLIPSIS
+    };
+    let spans = hits.iter()
+        .filter(|(hit_start, hit_end)| *hit_start >= start && *hit_end <= end)
+        .map(|(hit_start, hit_end)| (hit_start - start + prefix.len(), hit_end - start + prefix.len()))
+        .collect();
+
+    (format!("{}{}{}", prefix, &text[start..end], suffix), spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{
+        index::index_source,
+        sources::SourceFile
+    };
+
+    static PYTHON_CODE: &str = "class Gateway:
+    \"\"\"
+    Charges Credit
+    cards through the bank.
+    \"\"\"
+
+    def refund(self, charge_id: str):
+        \"\"\"Refunds a credit card charge.\"\"\"
+        pass
+";
+
+    #[test]
+    fn test_parse_query() {
+        let query = DocQuery::parse("Refund \"credit   card\" charge");
+        let expected = vec![
+            String::from("refund"),
+            String::from("credit card"),
+            String::from("charge")
+        ];
+
+        assert_eq!(query.terms, expected);
+    }
+
+    #[test]
+    fn test_find_phrase_across_lines() {
+        let query = DocQuery::parse("\"credit cards\"");
+        let (score, excerpt, spans) = query.find("Charges Credit\n    cards through the bank.").unwrap();
+
+        assert_eq!(score, 1);
+        assert_eq!(excerpt, "Charges Credit cards through the bank.");
+        assert_eq!(spans, vec![(8, 20)]);
+    }
+
+    #[test]
+    fn test_find_requires_all_terms() {
+        let query = DocQuery::parse("bank refund");
+        assert_eq!(query.find("Charges cards through the bank."), None);
+    }
+
+    #[test]
+    fn test_search_class_and_method_docstrings() {
+        let source = SourceFile::new(String::from("./gateway.py"), PYTHON_CODE.to_string());
+        let indexed = index_source(&source).remove(0);
+
+        let found_docs = search_class(&indexed, &DocQuery::parse("credit"));
+        assert_eq!(found_docs.len(), 2);
+        assert_eq!(found_docs[0].method_name, None);
+        assert_eq!(found_docs[0].location, "./gateway.py:1");
+        assert_eq!(found_docs[1].method_name, Some(String::from("refund")));
+        assert_eq!(found_docs[1].location, "./gateway.py:7");
+        assert_eq!(found_docs[1].excerpt, "Refunds a credit card charge.");
+    }
+}
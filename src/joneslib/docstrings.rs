@@ -1,16 +1,28 @@
-use super::{DOCSTRING, NEWLINE, INIT_DEF};
+use super::{DOCSTRING, NEWLINE, FUNCTION_DEF, ASYNC_KEYWORD};
 
 
-pub fn extract_docstring(code_block: &Vec<String>) -> Option<String> {
-    let docstring_vec = match get_docstring(&code_block) {
-        Some(docstring) => docstring,
-        None => return None
-    };
+pub fn extract_docstring(code_block: &[String]) -> Option<String> {
+    let docstring_vec = get_docstring(code_block)?;
 
-    return Some(docstring_vec.join(NEWLINE))
+    Some(docstring_vec.join(NEWLINE))
 }
 
-fn get_docstring(code_block: &Vec<String>) -> Option<Vec<String>> {
+/// Extract the docstring of a function or method out of its code block
+///
+/// The docstring has to be the first statement written after the header.
+pub fn extract_function_docstring(function_code: &[String]) -> Option<String> {
+    let header_end = function_code.iter()
+        .position(|line| line.trim_end().ends_with(':'))?;
+    let body = &function_code[header_end + 1..];
+    let first_statement = body.iter().find(|line| !line.trim().is_empty())?;
+    if !first_statement.trim_start().starts_with(DOCSTRING) {
+        return None
+    }
+
+    extract_docstring(body)
+}
+
+fn get_docstring(code_block: &[String]) -> Option<Vec<String>> {
     let mut start_docstring: bool = false;
     let mut docstring_vec: Vec<String> = Vec::new();
 
@@ -22,7 +34,7 @@ fn get_docstring(code_block: &Vec<String>) -> Option<Vec<String>> {
                 2 => break,
                 _ => continue
             }
-        } else if line.contains(INIT_DEF) {
+        } else if is_function_header(line) {
             break
         }
 
@@ -41,8 +53,14 @@ fn get_docstring(code_block: &Vec<String>) -> Option<Vec<String>> {
     }
 }
 
+fn is_function_header(line: &str) -> bool {
+    line.trim_start()
+        .trim_start_matches(ASYNC_KEYWORD)
+        .starts_with(FUNCTION_DEF)
+}
+
 fn format_line(line: &str) -> String {
-    return line.trim().replace(DOCSTRING, "")
+    line.trim().replace(DOCSTRING, "")
 }
 
 #[cfg(test)]
@@ -95,7 +113,7 @@ mod tests {
             "        self.name = name".to_string(),
             "".to_string()
         ];
-        let expected = vec![
+        let expected = [
             "".to_string(),
             "DocString".to_string(),
             "Some more test".to_string(),
@@ -119,4 +137,29 @@ mod tests {
 
         assert_eq!(extract_docstring(&test_code_block), None);
     }
+
+    #[test]
+    fn test_extract_docstring_skips_method_docstrings(){
+        let test_code_block: Vec<String> = vec![
+            "class God:".to_string(),
+            "    async def pray(self):".to_string(),
+            "        \"\"\"Docstring test\"\"\"".to_string(),
+            "".to_string()
+        ];
+
+        assert_eq!(extract_docstring(&test_code_block), None);
+    }
+
+    #[test]
+    fn test_extract_function_docstring(){
+        let test_code_block: Vec<String> = vec![
+            "    def pray(self,".to_string(),
+            "             god: str):".to_string(),
+            "        \"\"\"Docstring test\"\"\"".to_string(),
+            "        return god".to_string()
+        ];
+
+        assert_eq!(extract_function_docstring(&test_code_block), Some("Docstring test".to_string()));
+        assert_eq!(extract_function_docstring(&test_code_block[..2]), None);
+    }
 }
\ No newline at end of file
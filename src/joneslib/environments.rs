@@ -0,0 +1,124 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VENV_CONFIG: &str = "pyvenv.cfg";
+const VERSION_KEYS: [&str; 2] = ["version", "version_info"];
+const SITE_PACKAGES: &str = "site-packages";
+const LIB_DIRS: [&str; 3] = ["lib", "lib64", "Lib"];
+const INTERPRETERS: [&str; 2] = ["python", "pypy"];
+const PYTHON_PATH: &str = "PYTHONPATH";
+
+/// The directories listed in the `PYTHONPATH` environment variable, in order
+pub fn python_path() -> Vec<PathBuf> {
+    env::var_os(PYTHON_PATH)
+        .map(|value| search_path_entries(&value))
+        .unwrap_or_default()
+}
+
+/// Splits a search path written with the platform separator, skipping the empty entries
+fn search_path_entries(value: &OsStr) -> Vec<PathBuf> {
+    env::split_paths(value)
+        .filter(|entry| !entry.as_os_str().is_empty())
+        .collect()
+}
+
+/// Finds the `site-packages` directory of a virtual environment, without
+/// running its interpreter
+///
+/// The Python version written in `pyvenv.cfg` picks `lib/pythonX.Y/site-packages`,
+/// falling back to any `lib/*/site-packages` and to the Windows `Lib/site-packages`.
+///
+/// # Arguments
+///
+/// * `env_path` - The root of the virtual environment, e.g. `.venv`
+///
+/// # Errors
+///
+/// Returns an error if the directory is not a virtual environment or has no `site-packages`
+pub fn site_packages(env_path: &Path) -> Result<PathBuf, String> {
+    let config = fs::read_to_string(env_path.join(VENV_CONFIG)).map_err(|_| {
+        format!("{} is not a virtual environment, {} is missing", env_path.display(), VENV_CONFIG)
+    })?;
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(version) = python_version(&config) {
+        for lib_dir in LIB_DIRS.iter() {
+            for interpreter in INTERPRETERS.iter() {
+                candidates.push(env_path.join(lib_dir).join(format!("{}{}", interpreter, version)).join(SITE_PACKAGES));
+            }
+        }
+    }
+    for lib_dir in LIB_DIRS.iter() {
+        let mut versioned: Vec<PathBuf> = fs::read_dir(env_path.join(lib_dir))
+            .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| entry.path().join(SITE_PACKAGES)).collect())
+            .unwrap_or_default();
+        versioned.sort();
+        candidates.extend(versioned);
+        candidates.push(env_path.join(lib_dir).join(SITE_PACKAGES));
+    }
+
+    candidates.into_iter()
+        .find(|candidate| candidate.is_dir())
+        .ok_or_else(|| format!("No {} directory found in {}", SITE_PACKAGES, env_path.display()))
+}
+
+/// The `major.minor` Python version the environment was created with
+fn python_version(config: &str) -> Option<String> {
+    config.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| VERSION_KEYS.contains(&key.trim()))
+        .map(|(_, version)| version.trim().split('.').take(2).collect::<Vec<&str>>().join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_packages() {
+        let test_dir = "./testing_venv";
+        fs::create_dir_all(format!("{}/lib/python3.9/site-packages", test_dir)).expect("Could not write dir");
+        fs::create_dir_all(format!("{}/lib/python3.11/site-packages", test_dir)).expect("Could not write dir");
+        fs::write(format!("{}/pyvenv.cfg", test_dir), "home = /usr/bin\nversion = 3.11.4\n").unwrap();
+
+        let found = site_packages(Path::new(test_dir));
+        fs::write(format!("{}/pyvenv.cfg", test_dir), "home = /usr/bin\n").unwrap();
+        let without_version = site_packages(Path::new(test_dir));
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        assert_eq!(found, Ok(PathBuf::from(test_dir).join("lib/python3.11/site-packages")));
+        assert_eq!(without_version, Ok(PathBuf::from(test_dir).join("lib/python3.11/site-packages")));
+    }
+
+    #[test]
+    fn test_site_packages_not_an_env() {
+        assert!(site_packages(Path::new("./testing_missing_venv")).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_search_path_entries() {
+        assert_eq!(
+            search_path_entries(OsStr::new("src::/opt/libs")),
+            vec![PathBuf::from("src"), PathBuf::from("/opt/libs")]
+        );
+    }
+
+    #[test]
+    fn test_python_version() {
+        assert_eq!(python_version("home = /usr\nversion_info = 3.12.1.final.0\n"), Some(String::from("3.12")));
+        assert_eq!(python_version("home = /usr\n"), None);
+    }
+}
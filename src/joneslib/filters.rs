@@ -0,0 +1,181 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use super::{
+    objects::IndexedClass,
+    query::Query,
+    utils::{decorator_name, extract_class_keywords}
+};
+
+const METACLASS_KEYWORD: &str = "metaclass";
+
+/// Filters narrowing the grep results by what is written around the class name
+///
+/// A class passes the filter only if it matches all the given constraints,
+/// the query expression given with `--where` included.
+#[derive(Debug, Default)]
+pub struct ClassFilter {
+    pub bases: Vec<String>,
+    pub decorators: Vec<String>,
+    pub metaclass: Option<String>,
+    pub query: Option<Query>
+}
+impl ClassFilter {
+    pub fn new(bases: Vec<String>, decorators: Vec<String>, metaclass: Option<String>, query: Option<Query>) -> Self {
+        ClassFilter {
+            bases,
+            decorators,
+            metaclass,
+            query
+        }
+    }
+
+    /// Checks if the class inherits every base, has every decorator, uses the
+    /// metaclass and satisfies the query
+    ///
+    /// # Arguments
+    ///
+    /// * `indexed` - The class found in the project
+    /// * `module_path` - The path of the module defining the class, relative to its search root
+    pub fn matches(&self, indexed: &IndexedClass, module_path: &str) -> bool {
+        let bases_match = self.bases.iter()
+            .all(|base| has_base(indexed, base));
+        let decorators_match = self.decorators.iter()
+            .all(|decorator| has_decorator(indexed, decorator));
+        let metaclass_match = match &self.metaclass {
+            Some(metaclass) => has_metaclass(indexed, metaclass),
+            None => true
+        };
+        let query_match = match &self.query {
+            Some(query) => query.matches(indexed, module_path),
+            None => true
+        };
+
+        bases_match && decorators_match && metaclass_match && query_match
+    }
+}
+
+/// Checks if the class directly inherits the base, written with or without its module path
+pub fn has_base(indexed: &IndexedClass, base: &str) -> bool {
+    indexed.class.inheritance.iter()
+        .filter(|inherited| !inherited.contains('='))
+        .any(|inherited| same_name(inherited, base))
+}
+
+/// Checks if the class is decorated with the decorator, ignoring its call arguments
+pub fn has_decorator(indexed: &IndexedClass, decorator: &str) -> bool {
+    let decorator = decorator.trim_start_matches('@');
+    indexed.decorators.iter()
+        .any(|written| same_name(written.split('(').next().unwrap_or(written), decorator)
+            || decorator_name(written) == decorator)
+}
+
+/// Checks if the class header sets the metaclass keyword to the given class
+pub fn has_metaclass(indexed: &IndexedClass, metaclass: &str) -> bool {
+    extract_class_keywords(&indexed.header).iter()
+        .any(|(keyword, value)| keyword == METACLASS_KEYWORD && same_name(value, metaclass))
+}
+
+/// Compares a name as written in code with the searched one. Generic
+/// subscripts are ignored and the module path is optional.
+///
+/// # Example
+/// `typing.Generic[T]` is the same as `Generic` and `typing.Generic`
+fn same_name(written: &str, searched: &str) -> bool {
+    let written = written.split('[').next().unwrap_or(written).trim();
+    let short_name = written.rsplit('.').next().unwrap_or(written);
+    written == searched || short_name == searched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{
+        index::index_source,
+        sources::SourceFile
+    };
+
+    static PYTHON_CODE: &str = "@handlers.register('orders')
+class OrderHandler(base.BaseHandler, Generic[T], metaclass=Singleton):
+    pass
+
+class CartHandler(BaseHandler):
+    pass
+";
+
+    fn classes() -> Vec<IndexedClass> {
+        let source = SourceFile::new(String::from("./handlers.py"), PYTHON_CODE.to_string());
+        index_source(&source)
+    }
+
+    fn filtered(filter: &ClassFilter) -> Vec<String> {
+        classes().iter()
+            .filter(|indexed| filter.matches(indexed, "handlers.py"))
+            .map(|indexed| indexed.class.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_by_base() {
+        let filter = ClassFilter::new(vec![String::from("BaseHandler")], Vec::new(), None, None);
+        assert_eq!(filtered(&filter), vec!["OrderHandler", "CartHandler"]);
+
+        let filter = ClassFilter::new(vec![String::from("base.BaseHandler"), String::from("Generic")], Vec::new(), None, None);
+        assert_eq!(filtered(&filter), vec!["OrderHandler"]);
+    }
+
+    #[test]
+    fn test_filter_by_decorator_and_metaclass() {
+        let filter = ClassFilter::new(Vec::new(), vec![String::from("@register")], None, None);
+        assert_eq!(filtered(&filter), vec!["OrderHandler"]);
+
+        let filter = ClassFilter::new(Vec::new(), Vec::new(), Some(String::from("Singleton")), None);
+        assert_eq!(filtered(&filter), vec!["OrderHandler"]);
+
+        let filter = ClassFilter::new(vec![String::from("Singleton")], Vec::new(), None, None);
+        assert!(filtered(&filter).is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_query() {
+        let query = Query::parse("not decorator:register").unwrap();
+        let filter = ClassFilter::new(vec![String::from("BaseHandler")], Vec::new(), None, Some(query));
+
+        assert_eq!(filtered(&filter), vec!["CartHandler"]);
+    }
+
+    #[test]
+    fn test_empty_filter_matches_all() {
+        assert_eq!(filtered(&ClassFilter::default()).len(), 2);
+    }
+
+    #[test]
+    fn test_has_base() {
+        let classes = classes();
+
+        assert!(has_base(&classes[0], "BaseHandler"));
+        assert!(has_base(&classes[0], "base.BaseHandler"));
+        assert!(has_base(&classes[0], "Generic"));
+        assert!(has_base(&classes[1], "BaseHandler"));
+        assert!(!has_base(&classes[1], "base.BaseHandler"));
+        assert!(!has_base(&classes[0], "Singleton"));
+    }
+
+    #[test]
+    fn test_has_decorator_and_metaclass() {
+        let classes = classes();
+
+        assert!(has_decorator(&classes[0], "@register"));
+        assert!(has_decorator(&classes[0], "handlers.register"));
+        assert!(!has_decorator(&classes[1], "register"));
+        assert!(has_metaclass(&classes[0], "Singleton"));
+        assert!(!has_metaclass(&classes[1], "Singleton"));
+    }
+}
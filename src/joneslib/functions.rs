@@ -0,0 +1,229 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::sync::LazyLock;
+use regex::Regex;
+use super::{
+    FUNCTION_DEF, ASYNC_KEYWORD, DOCSTRING,
+    docstrings::extract_function_docstring,
+    objects::{Constant, IndexedFunction, Method, Parameter},
+    sources::SourceFile,
+    utils::{bracket_depth, extract_decorators, header_end, strip_comment}
+};
+
+const DEFAULT_TYPE: &str = "None";
+const HEADER_END: char = ':';
+
+static CONSTANT_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Z_][A-Z0-9_]*)\s*(?::\s*([^=]+?))?\s*=\s*([^=].*)$").unwrap()
+});
+
+/// Extracts every function defined at the module level of a Python file
+///
+/// # Arguments
+///
+/// * `source` - The Python file previously read
+pub fn index_functions(source: &SourceFile) -> Vec<IndexedFunction> {
+    let lines = source.lines();
+    let mut functions: Vec<IndexedFunction> = Vec::new();
+
+    for (counter, line) in lines.iter().enumerate() {
+        let definition = line.strip_prefix(ASYNC_KEYWORD).unwrap_or(line);
+        if !definition.starts_with(FUNCTION_DEF) {
+            continue
+        }
+        let function_code = cut_function_block(&lines, counter);
+
+        // Method headers are parsed with a leading space, the same way
+        // they are found inside a class body
+        let mut header = String::new();
+        for header_line in function_code.iter() {
+            header.push(' ');
+            let header_line = strip_comment(header_line).trim();
+            header.push_str(header_line.strip_prefix(ASYNC_KEYWORD).unwrap_or(header_line));
+            if let Some(end) = header_end(&header) {
+                header.truncate(end + 1);
+                break
+            }
+        }
+
+        let (file_path, line) = source.locate(counter + 1);
+        functions.push(IndexedFunction::new(
+            Method::new(&header),
+            extract_decorators(&lines, counter),
+            extract_function_docstring(&function_code),
+            file_path,
+            line
+        ));
+    }
+    functions
+}
+
+/// Extracts the constants assigned at the module level of a Python file,
+/// which are the names written in upper case, e.g. `MAX_RETRIES: int = 3`
+///
+/// The assignments written inside docstrings are skipped, and a value
+/// spanning several lines is joined into one.
+///
+/// # Arguments
+///
+/// * `source` - The Python file previously read
+pub fn extract_constants(source: &SourceFile) -> Vec<Constant> {
+    let lines = source.lines();
+    let mut constants: Vec<Constant> = Vec::new();
+    let mut in_docstring: bool = false;
+    let mut counter: usize = 0;
+
+    while counter < lines.len() {
+        let line = lines[counter].trim_end();
+        let start = counter;
+        counter += 1;
+        let opens_docstring = line.matches(DOCSTRING).count() % 2 == 1;
+        let captures = match CONSTANT_ASSIGNMENT.captures(line) {
+            Some(captures) if !in_docstring => captures,
+            _ => {
+                in_docstring ^= opens_docstring;
+                continue
+            }
+        };
+        let name = &captures[1];
+        let mut value = captures[3].trim().to_string();
+        while counter < lines.len() && (bracket_depth(&value) > 0 || value.matches(DOCSTRING).count() % 2 == 1) {
+            join_line(&mut value, lines[counter]);
+            counter += 1;
+        }
+        if !name.contains(|chr: char| chr.is_ascii_uppercase()) {
+            continue
+        }
+        let static_type = captures.get(2)
+            .map(|annotation| annotation.as_str().to_string())
+            .unwrap_or_else(|| DEFAULT_TYPE.to_string());
+        let (file_path, line) = source.locate(start + 1);
+
+        constants.push(Constant::new(
+            Parameter::new(name.to_string(), static_type),
+            value,
+            file_path,
+            line
+        ));
+    }
+    constants
+}
+
+/// Appends a continuation line to a value, without a space after an opened
+/// or before a closed bracket
+fn join_line(value: &mut String, line: &str) {
+    let line = line.trim();
+    if !value.ends_with(['(', '[', '{']) && !line.starts_with([')', ']', '}']) {
+        value.push(' ');
+    }
+    value.push_str(line);
+}
+
+/// Cuts the code block of a module level function, from its header to the
+/// last line of its body
+fn cut_function_block(lines: &[&str], start: usize) -> Vec<String> {
+    let mut header_done: bool = false;
+    let mut function_code: Vec<String> = Vec::new();
+
+    for line in lines[start..].iter() {
+        let line = line.trim_end_matches('\r');
+        if header_done && !line.trim().is_empty() && !line.starts_with(char::is_whitespace) {
+            break
+        }
+        if !header_done && (strip_comment(line).trim_end().ends_with(HEADER_END)
+            || header_end(line).is_some()) {
+            header_done = true;
+        }
+        function_code.push(line.to_string());
+    }
+    while function_code.last().map(|line| line.trim().is_empty()).unwrap_or(false) {
+        function_code.pop();
+    }
+    function_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PYTHON_CODE: &str = "MAX_RETRIES: int = 3
+DEFAULT_CURRENCY = 'EUR'
+_CACHE = {}
+logger = getLogger()
+\"\"\"
+TIMEOUT = 30 is not a constant inside a docstring
+\"\"\"
+CURRENCIES = [
+    'EUR',
+    'USD',
+]
+
+@retry(MAX_RETRIES)
+def send_invoice(invoice: Invoice,
+                 force: bool) -> bool:
+    \"\"\"Sends an invoice to the customer.\"\"\"
+    return True
+
+async def refund(charge_id: str): ...
+
+class Invoice:
+    def total(self) -> int:
+        return 0
+";
+
+    fn source() -> SourceFile {
+        SourceFile::new(String::from("./billing.py"), PYTHON_CODE.to_string())
+    }
+
+    #[test]
+    fn test_index_functions() {
+        let functions = index_functions(&source());
+
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].function.name, "send_invoice");
+        assert_eq!(functions[0].function.output, "bool");
+        assert_eq!(functions[0].function.parameters, vec![
+            Parameter::new(String::from("invoice"), String::from("Invoice")),
+            Parameter::new(String::from("force"), String::from("bool")),
+        ]);
+        assert_eq!(functions[0].decorators, vec!["retry(MAX_RETRIES)"]);
+        assert_eq!(functions[0].docstring, Some(String::from("Sends an invoice to the customer.")));
+        assert_eq!(functions[0].location(), "./billing.py:14");
+        assert_eq!(functions[1].function.name, "refund");
+        assert_eq!(functions[1].function.output, "None");
+        assert_eq!(functions[1].docstring, None);
+    }
+
+    #[test]
+    fn test_extract_constants() {
+        let constants = extract_constants(&source());
+        let expected = vec![
+            Constant::new(
+                Parameter::new(String::from("MAX_RETRIES"), String::from("int")),
+                String::from("3"), String::from("./billing.py"), 1
+            ),
+            Constant::new(
+                Parameter::new(String::from("DEFAULT_CURRENCY"), String::from("None")),
+                String::from("'EUR'"), String::from("./billing.py"), 2
+            ),
+            Constant::new(
+                Parameter::new(String::from("_CACHE"), String::from("None")),
+                String::from("{}"), String::from("./billing.py"), 3
+            ),
+            Constant::new(
+                Parameter::new(String::from("CURRENCIES"), String::from("None")),
+                String::from("['EUR', 'USD',]"), String::from("./billing.py"), 8
+            ),
+        ];
+
+        assert_eq!(constants, expected);
+    }
+}
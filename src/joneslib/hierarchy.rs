@@ -0,0 +1,262 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::collections::HashSet;
+use super::{
+    objects::Method,
+    project::Project
+};
+
+/// A class taking part in a method resolution order
+#[derive(Debug, Clone, PartialEq)]
+pub enum MroEntry {
+    Class(usize),
+    External(String)
+}
+impl MroEntry {
+    /// The class name as defined in the project or as written for external bases
+    pub fn name(&self, project: &Project) -> String {
+        match self {
+            MroEntry::Class(class_id) => project.classes[*class_id].class.name.clone(),
+            MroEntry::External(name) => name.clone()
+        }
+    }
+}
+
+/// A method available on a class together with the ancestor method it overrides
+#[derive(Debug)]
+pub struct InheritedMethod<'a> {
+    pub method: &'a Method,
+    pub overrides: Option<String>
+}
+
+/// The methods a class gets from one of the classes in its resolution order
+#[derive(Debug)]
+pub struct MethodGroup<'a> {
+    pub entry: MroEntry,
+    pub methods: Vec<InheritedMethod<'a>>
+}
+
+/// Computes the C3 method resolution order of a class, the same way Python does
+///
+/// Bases defined outside of the project are kept as external entries
+/// without ancestors of their own.
+///
+/// # Errors
+///
+/// Returns a message if the hierarchy has a cycle or if no consistent order exists
+pub fn linearize(project: &Project, class_id: usize) -> Result<Vec<MroEntry>, String> {
+    let mut visiting: HashSet<usize> = HashSet::new();
+    linearize_entry(project, MroEntry::Class(class_id), &mut visiting)
+}
+
+/// Groups the methods available on a class by the class in the resolution
+/// order that defines them. Methods overridden by an earlier class are left
+/// out of the later groups.
+///
+/// # Arguments
+///
+/// * `project` - The project the resolution order was computed for
+/// * `mro` - The method resolution order of the inspected class
+pub fn method_groups<'a>(project: &'a Project, mro: &[MroEntry]) -> Vec<MethodGroup<'a>> {
+    let mut provided: HashSet<&str> = HashSet::new();
+    let mut groups: Vec<MethodGroup> = Vec::new();
+
+    for (position, entry) in mro.iter().enumerate() {
+        let class_id = match entry {
+            MroEntry::Class(class_id) => *class_id,
+            MroEntry::External(_) => {
+                groups.push(MethodGroup { entry: entry.clone(), methods: Vec::new() });
+                continue
+            }
+        };
+        let methods: Vec<InheritedMethod> = project.classes[class_id].class.methods.iter()
+            .filter(|method| !provided.contains(method.name.as_str()))
+            .map(|method| InheritedMethod {
+                method,
+                overrides: overridden_in(project, &mro[position + 1..], &method.name)
+            })
+            .collect();
+        provided.extend(methods.iter().map(|inherited| inherited.method.name.as_str()));
+        groups.push(MethodGroup { entry: entry.clone(), methods });
+    }
+    groups
+}
+
+/// The first class of the remaining resolution order defining the method
+fn overridden_in(project: &Project, mro: &[MroEntry], method_name: &str) -> Option<String> {
+    mro.iter()
+        .find(|entry| match entry {
+            MroEntry::Class(class_id) => project.classes[*class_id].class.methods.iter()
+                .any(|method| method.name == method_name),
+            MroEntry::External(_) => false
+        })
+        .map(|entry| entry.name(project))
+}
+
+fn linearize_entry(project: &Project, entry: MroEntry, visiting: &mut HashSet<usize>) -> Result<Vec<MroEntry>, String> {
+    let class_id = match entry {
+        MroEntry::Class(class_id) => class_id,
+        MroEntry::External(_) => return Ok(vec![entry])
+    };
+    if !visiting.insert(class_id) {
+        return Err(format!("Inheritance cycle found at class {}", entry.name(project)))
+    }
+
+    let bases: Vec<MroEntry> = project.bases(class_id).iter()
+        .map(|base| match project.resolve_base(class_id, base) {
+            Some(base_id) => MroEntry::Class(base_id),
+            None => MroEntry::External(base.split('[').next().unwrap_or(base).trim().to_string())
+        })
+        .collect();
+
+    let mut sequences: Vec<Vec<MroEntry>> = Vec::new();
+    for base in bases.iter() {
+        sequences.push(linearize_entry(project, base.clone(), visiting)?);
+    }
+    sequences.push(bases);
+    visiting.remove(&class_id);
+
+    let mut linearization = vec![entry.clone()];
+    match merge(sequences) {
+        Some(merged) => linearization.extend(merged),
+        None => return Err(format!(
+            "Cannot create a consistent method resolution order for class {}", entry.name(project)
+        ))
+    }
+    Ok(linearization)
+}
+
+/// The C3 merge: repeatedly takes the first head that is not found in the
+/// tail of any other sequence
+fn merge(mut sequences: Vec<Vec<MroEntry>>) -> Option<Vec<MroEntry>> {
+    let mut merged: Vec<MroEntry> = Vec::new();
+    loop {
+        sequences.retain(|sequence| !sequence.is_empty());
+        if sequences.is_empty() {
+            return Some(merged)
+        }
+
+        let head = sequences.iter()
+            .map(|sequence| &sequence[0])
+            .find(|candidate| sequences.iter().all(|sequence| !sequence[1..].contains(candidate)))?
+            .clone();
+        for sequence in sequences.iter_mut() {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+        merged.push(head);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::sources::SourceFile;
+
+    fn diamond_project() -> Project {
+        let sources = vec![
+            SourceFile::new(String::from("./shapes.py"), String::from(
+"class Shape(ABC):
+    def area(self) -> float:
+        pass
+
+    def name(self) -> str:
+        pass
+
+class Rounded(Shape):
+    def area(self) -> float:
+        pass
+
+class Colored(Shape):
+    def paint(self):
+        pass
+
+class Circle(Rounded, Colored):
+    def name(self) -> str:
+        pass
+"
+            )),
+            SourceFile::new(String::from("./broken.py"), String::from(
+"class A:
+    pass
+
+class B(A):
+    pass
+
+class C(A, B):
+    pass
+"
+            )),
+        ];
+        Project::new(Path::new("."), sources)
+    }
+
+    #[test]
+    fn test_linearize_diamond() {
+        let project = diamond_project();
+        let mro = linearize(&project, project.find_class("Circle").unwrap()).unwrap();
+        let names: Vec<String> = mro.iter().map(|entry| entry.name(&project)).collect();
+
+        assert_eq!(names, vec!["Circle", "Rounded", "Colored", "Shape", "ABC"]);
+        assert_eq!(mro[4], MroEntry::External(String::from("ABC")));
+    }
+
+    #[test]
+    fn test_linearize_base_named_as_the_class() {
+        let sources = vec![
+            SourceFile::new(String::from("./pkg/m.py"), String::from("class Worker:\n    def run(self):\n        pass\n")),
+            SourceFile::new(String::from("./pkg/w.py"), String::from(
+                "from pkg.m import Worker\n\nclass Worker(Worker):\n    def stop(self):\n        pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        let class_id = project.find_class("pkg.w.Worker").unwrap();
+        let base_id = project.find_class("pkg.m.Worker").unwrap();
+        let mro = linearize(&project, class_id).unwrap();
+
+        assert_eq!(mro, vec![MroEntry::Class(class_id), MroEntry::Class(base_id)]);
+        let groups = method_groups(&project, &mro);
+        assert_eq!(groups[1].methods[0].method.name, "run");
+        assert_eq!(project.ancestors(class_id)[0].location, Some(project.classes[base_id].location()));
+        assert_eq!(project.subclasses(base_id)[0].location, project.classes[class_id].location());
+    }
+
+    #[test]
+    fn test_linearize_inconsistent() {
+        let project = diamond_project();
+        assert!(linearize(&project, project.find_class("C").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_method_groups() {
+        let project = diamond_project();
+        let mro = linearize(&project, project.find_class("Circle").unwrap()).unwrap();
+        let groups = method_groups(&project, &mro);
+
+        let names: Vec<String> = groups.iter().map(|group| group.entry.name(&project)).collect();
+        assert_eq!(names, vec!["Circle", "Rounded", "Colored", "Shape", "ABC"]);
+
+        let methods: Vec<Vec<(&str, Option<&str>)>> = groups.iter()
+            .map(|group| group.methods.iter()
+                .map(|inherited| (inherited.method.name.as_str(), inherited.overrides.as_deref()))
+                .collect())
+            .collect();
+        assert_eq!(methods, vec![
+            vec![("name", Some("Shape"))],
+            vec![("area", Some("Shape"))],
+            vec![("paint", None)],
+            vec![],
+            vec![],
+        ]);
+    }
+}
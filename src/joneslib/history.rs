@@ -0,0 +1,174 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::fs;
+use std::path::Path;
+use super::{
+    is_python_file,
+    index::index_source,
+    objects::{Commit, IndexedClass, MethodHistory, PythonClass},
+    project::is_nested,
+    revisions::{file_history, read_objects},
+    sources::SourceFile
+};
+
+/// Finds when each method of a class was introduced and when its signature
+/// last changed, by extracting the class from its file at every commit of
+/// the git log, oldest first
+///
+/// A method removed and added back later counts as introduced again, and
+/// the commits changing only the body of a method are not reported. The
+/// working directory version is read from the file again, so the signatures
+/// merged from a `.pyi` stub are not taken for uncommitted changes.
+///
+/// # Arguments
+///
+/// * `indexed` - The class, as found in the working directory
+///
+/// # Output
+///
+/// The history of each method of the class in the working directory, in order
+///
+/// # Errors
+///
+/// Returns an error if the class is not defined in a Python file tracked by git
+pub fn class_history(indexed: &IndexedClass) -> Result<Vec<MethodHistory>, String> {
+    let file_path = Path::new(&indexed.file_path);
+    if !is_python_file(file_path) || !file_path.is_file() {
+        return Err(format!("{} is not a Python file tracked by git", indexed.file_path))
+    }
+    let revisions = file_history(file_path)?;
+    let objects: Vec<String> = revisions.iter()
+        .map(|(commit, path)| format!("{}:{}", commit.hash, path))
+        .collect();
+    let dir_path = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let contents = read_objects(dir_path, &objects)
+        .ok_or_else(|| format!("Could not read the git history of {}", indexed.file_path))?;
+
+    let mut history: Vec<MethodHistory> = Vec::new();
+    for ((commit, path), content) in revisions.iter().zip(contents).rev() {
+        let class = content.and_then(|content| class_at_revision(indexed, path, content));
+        history = track_methods(history, class.as_ref(), Some(commit));
+    }
+    let current = fs::read_to_string(file_path).ok()
+        .and_then(|content| class_at_revision(indexed, &indexed.file_path, content));
+    Ok(track_methods(history, current.as_ref(), None))
+}
+
+/// The class in the content of its file at a revision, matched by name and nesting
+fn class_at_revision(indexed: &IndexedClass, path: &str, content: String) -> Option<PythonClass> {
+    index_source(&SourceFile::new(path.to_string(), content))
+        .into_iter()
+        .find(|found| found.class.name == indexed.class.name && is_nested(found) == is_nested(indexed))
+        .map(|found| found.class)
+}
+
+/// Moves the history of the methods one version of the class forward
+///
+/// # Arguments
+///
+/// * `history` - The history of the methods of the previous version
+/// * `class` - The next version of the class, `None` if it did not exist
+/// * `commit` - The commit of the next version, `None` for the working directory
+fn track_methods(mut history: Vec<MethodHistory>, class: Option<&PythonClass>, commit: Option<&Commit>) -> Vec<MethodHistory> {
+    let methods = match class {
+        Some(class) => &class.methods,
+        None => return Vec::new()
+    };
+
+    methods.iter()
+        .map(|method| {
+            let signature = method.signature();
+            match history.iter().position(|tracked| tracked.name == method.name) {
+                Some(position) => {
+                    let mut tracked = history.swap_remove(position);
+                    if tracked.signature != signature {
+                        match commit {
+                            Some(commit) => tracked.changed = Some(commit.clone()),
+                            None => tracked.uncommitted = true
+                        }
+                        tracked.signature = signature;
+                    }
+                    tracked
+                },
+                None => MethodHistory::new(method.name.clone(), signature, commit.cloned())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{revisions::tests::run_git, stubs::merge_stub};
+
+    #[test]
+    fn test_class_history() {
+        let test_dir = "./testing_history";
+        let file_path = format!("{}/shop/cart.py", test_dir);
+        fs::create_dir_all(format!("{}/shop", test_dir)).expect("Could not write dir");
+        run_git(test_dir, &["init", "--quiet"]);
+        fs::write(&file_path, "class Cart:\n    def add(self, item):\n        pass\n\n    def clear(self):\n        pass\n").unwrap();
+        run_git(test_dir, &["add", "."]);
+        run_git(test_dir, &["commit", "--quiet", "-m", "Add the cart"]);
+        fs::write(&file_path, "class Cart:\n    def add(self, item, qty=1):\n        pass\n\n    def clear(self):\n        return None\n").unwrap();
+        run_git(test_dir, &["commit", "--quiet", "-am", "Add a quantity"]);
+        fs::write(&file_path, "class Cart:\n    def add(self, item, qty=1):\n        pass\n\n    def clear(self):\n        return None\n\n    def total(self):\n        pass\n").unwrap();
+
+        let indexed = index_source(&SourceFile::new(file_path.clone(), fs::read_to_string(&file_path).unwrap())).remove(0);
+        let history = class_history(&indexed);
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        let history = history.unwrap();
+        let summary = |commit: &Option<Commit>| commit.as_ref().map(|commit| commit.summary.clone());
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].name, "add");
+        assert_eq!(summary(&history[0].introduced), Some(String::from("Add the cart")));
+        assert_eq!(summary(&history[0].changed), Some(String::from("Add a quantity")));
+        assert_eq!(summary(&history[1].introduced), Some(String::from("Add the cart")));
+        assert_eq!(history[1].changed, None);
+        assert_eq!(history[2].introduced, None);
+        assert!(history[2].uncommitted);
+    }
+
+    #[test]
+    fn test_class_history_with_stub() {
+        let test_dir = "./testing_history_stub";
+        let file_path = format!("{}/rates.py", test_dir);
+        fs::create_dir_all(test_dir).expect("Could not write dir");
+        run_git(test_dir, &["init", "--quiet"]);
+        fs::write(&file_path, "class Rates:\n    def convert(self, amount, currency):\n        pass\n").unwrap();
+        run_git(test_dir, &["add", "."]);
+        run_git(test_dir, &["commit", "--quiet", "-m", "Add the rates"]);
+
+        let mut indexed = index_source(&SourceFile::new(file_path.clone(), fs::read_to_string(&file_path).unwrap())).remove(0);
+        let stub = index_source(&SourceFile::new(
+            format!("{}/rates.pyi", test_dir),
+            String::from("class Rates:\n    def convert(self, amount: float, currency: str) -> float: ...\n")
+        )).remove(0);
+        merge_stub(&mut indexed, stub);
+        let history = class_history(&indexed);
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        let history = history.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].changed, None);
+        assert!(!history[0].uncommitted);
+    }
+
+    #[test]
+    fn test_class_history_outside_git() {
+        let indexed = index_source(&SourceFile::new(
+            String::from("./wheelhouse/shop.whl!/shop/cart.py"), String::from("class Cart:\n    pass\n")
+        )).remove(0);
+
+        assert!(class_history(&indexed).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use super::{
+    objects::Import,
+    utils::strip_comment
+};
+
+const FROM_KEYWORD: &str = "from ";
+const IMPORT_KEYWORD: &str = "import ";
+const ALIAS_KEYWORD: &str = " as ";
+const LINE_CONTINUATION: char = '\\';
+const EXPORTS_NAME: &str = "__all__";
+
+/// Extracts the module level imports of a Python file
+///
+/// Relative imports are resolved against the module the file belongs to,
+/// so `from .base import Base as B` written in `billing/invoices.py` binds
+/// `B` to `Base` from `billing.base`.
+///
+/// # Arguments
+///
+/// * `lines` - The python file code lines previously read
+/// * `module_name` - The dotted name of the module defined by the file
+/// * `is_package` - If the file is the `__init__.py` of a package
+pub fn extract_imports(lines: &[&str], module_name: &str, is_package: bool) -> Vec<Import> {
+    let mut imports: Vec<Import> = Vec::new();
+
+    for (line_number, statement) in logical_lines(lines) {
+        if let Some(rest) = statement.strip_prefix(FROM_KEYWORD) {
+            let (source, names) = match rest.split_once(&format!(" {}", IMPORT_KEYWORD)) {
+                Some(parts) => parts,
+                None => continue
+            };
+            let module = match absolute_module(source.trim(), module_name, is_package) {
+                Some(module) => module,
+                None => continue
+            };
+            let names = names.trim()
+                .trim_start_matches('(')
+                .trim_end_matches(')');
+            for name in names.split(',') {
+                let (name, alias) = split_alias(name);
+                if name.is_empty() {
+                    continue
+                }
+                imports.push(Import::new(module.clone(), Some(name.clone()), alias, line_number));
+            }
+        } else if let Some(rest) = statement.strip_prefix(IMPORT_KEYWORD) {
+            for module in rest.split(',') {
+                let (module, alias) = split_alias(module);
+                if module.is_empty() {
+                    continue
+                }
+                imports.push(Import::new(module, None, alias, line_number));
+            }
+        }
+    }
+    imports
+}
+
+/// Extracts the names listed in the module level `__all__` of a Python file,
+/// including the ones added with `__all__ += [...]`
+///
+/// # Output
+///
+/// * `None` - if the file does not define `__all__`
+/// * `Some` - the exported names, in the order they are written
+pub fn extract_exports(lines: &[&str]) -> Option<Vec<String>> {
+    let mut exports: Option<Vec<String>> = None;
+    let mut open_list: bool = false;
+
+    for line in lines.iter() {
+        let code = strip_comment(line);
+        let listed = if open_list {
+            code
+        } else if let Some(rest) = code.strip_prefix(EXPORTS_NAME) {
+            match rest.trim_start().strip_prefix("+=").or_else(|| rest.trim_start().strip_prefix('=')) {
+                Some(listed) => listed,
+                None => continue
+            }
+        } else {
+            continue
+        };
+
+        let names = exports.get_or_insert_with(Vec::new);
+        for name in listed.split(',') {
+            let name = name.trim().trim_matches(|chr| "[]()".contains(chr)).trim();
+            let name = name.trim_matches(|chr| chr == '\'' || chr == '"');
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+        }
+        let opened = listed.matches(['[', '(']).count() as i32 - listed.matches([']', ')']).count() as i32;
+        open_list = match open_list {
+            true => !listed.contains([']', ')']),
+            false => opened > 0
+        };
+    }
+    exports
+}
+
+/// Builds the dotted module name of a file relative to the project root
+///
+/// # Example
+/// `billing/invoices.py` is `billing.invoices` and `billing/__init__.py` is `billing`
+pub fn module_name(relative_path: &str) -> String {
+    let without_extension = match relative_path.rsplit_once('.') {
+        Some((path, extension)) if !extension.contains(['/', '\\']) => path,
+        _ => relative_path
+    };
+    let parts: Vec<&str> = without_extension
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+
+    match parts.last() {
+        Some(&"__init__") => parts[..parts.len() - 1].join("."),
+        _ => parts.join(".")
+    }
+}
+
+/// Joins the import statements split over multiple lines with parentheses
+/// or backslashes, keeping the line number where each statement starts.
+/// Only the module level statements are kept.
+fn logical_lines(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut statements: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    let mut start: usize = 0;
+    let mut open_parentheses: i32 = 0;
+
+    for (counter, line) in lines.iter().enumerate() {
+        let code = strip_comment(line);
+        if current.is_empty() {
+            if !code.starts_with(FROM_KEYWORD) && !code.starts_with(IMPORT_KEYWORD) {
+                continue
+            }
+            start = counter + 1;
+        }
+        open_parentheses += code.matches('(').count() as i32 - code.matches(')').count() as i32;
+        current.push_str(code.trim().trim_end_matches(LINE_CONTINUATION));
+        current.push(' ');
+
+        if open_parentheses <= 0 && !code.trim_end().ends_with(LINE_CONTINUATION) {
+            statements.push((start, current.split_whitespace().collect::<Vec<&str>>().join(" ")));
+            current.clear();
+            open_parentheses = 0;
+        }
+    }
+    statements
+}
+
+/// Splits an imported name from its alias, if it has one
+fn split_alias(name: &str) -> (String, String) {
+    let name = name.trim();
+    match name.split_once(ALIAS_KEYWORD) {
+        Some((name, alias)) => (name.trim().to_string(), alias.trim().to_string()),
+        None => (name.to_string(), name.to_string())
+    }
+}
+
+/// Turns a relative import source into an absolute module name
+fn absolute_module(source: &str, module_name: &str, is_package: bool) -> Option<String> {
+    let level = source.chars().take_while(|chr| *chr == '.').count();
+    if level == 0 {
+        return Some(source.to_string())
+    }
+
+    let mut package: Vec<&str> = module_name.split('.').filter(|part| !part.is_empty()).collect();
+    if !is_package {
+        package.pop()?;
+    }
+    for _ in 1..level {
+        package.pop()?;
+    }
+    let relative = &source[level..];
+    if !relative.is_empty() {
+        package.push(relative);
+    }
+    Some(package.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let lines = vec![
+            "import os, typing as t",
+            "from .base import Base as B, Mixin  # comment",
+            "from ..core.models import (",
+            "    Model,",
+            "    Field as F,",
+            ")",
+            "def load():",
+            "    from json import loads",
+        ];
+        let expected = vec![
+            Import::new(String::from("os"), None, String::from("os"), 1),
+            Import::new(String::from("typing"), None, String::from("t"), 1),
+            Import::new(String::from("shop.billing.base"), Some(String::from("Base")), String::from("B"), 2),
+            Import::new(String::from("shop.billing.base"), Some(String::from("Mixin")), String::from("Mixin"), 2),
+            Import::new(String::from("shop.core.models"), Some(String::from("Model")), String::from("Model"), 3),
+            Import::new(String::from("shop.core.models"), Some(String::from("Field")), String::from("F"), 3),
+        ];
+
+        assert_eq!(extract_imports(&lines, "shop.billing.invoices", false), expected);
+    }
+
+    #[test]
+    fn test_import_statement() {
+        let lines = vec!["import os.path, typing as t", "from .base import Base as B, Mixin"];
+        let statements: Vec<String> = extract_imports(&lines, "shop.models", false).iter()
+            .map(|import| import.statement())
+            .collect();
+
+        assert_eq!(statements, vec![
+            "import os.path",
+            "import typing as t",
+            "from shop.base import Base as B",
+            "from shop.base import Mixin",
+        ]);
+    }
+
+    #[test]
+    fn test_extract_imports_in_package() {
+        let lines = vec!["from .invoices import Invoice"];
+        let expected = vec![
+            Import::new(String::from("shop.billing.invoices"), Some(String::from("Invoice")), String::from("Invoice"), 1),
+        ];
+
+        assert_eq!(extract_imports(&lines, "shop.billing", true), expected);
+    }
+
+    #[test]
+    fn test_extract_exports() {
+        let lines = vec![
+            "__all__ = ['Invoice', \"InvoiceService\"]",
+            "__all__ += (",
+            "    'Refund',  # added later",
+            ")",
+            "__all_names = ['Draft']",
+        ];
+        let expected = vec![
+            String::from("Invoice"),
+            String::from("InvoiceService"),
+            String::from("Refund")
+        ];
+
+        assert_eq!(extract_exports(&lines), Some(expected));
+        assert_eq!(extract_exports(&["import os"]), None);
+    }
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("billing/invoices.py"), "billing.invoices");
+        assert_eq!(module_name("./billing/__init__.py"), "billing");
+        assert_eq!(module_name("setup.py"), "setup");
+        assert_eq!(module_name("./billing"), "billing");
+    }
+}
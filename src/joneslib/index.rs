@@ -0,0 +1,141 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::path::Path;
+use super::{
+    build_python_class,
+    cut_class_block,
+    archives::member_import_path,
+    objects::IndexedClass,
+    sources::{SearchPath, SourceFile},
+    utils::{extract_class_name, extract_decorators}
+};
+
+/// Extracts every class defined in a source file, nested classes included
+///
+/// # Arguments
+///
+/// * `source` - The Python source file to be indexed
+pub fn index_source(source: &SourceFile) -> Vec<IndexedClass> {
+    let lines = source.lines();
+    let mut classes: Vec<IndexedClass> = Vec::new();
+
+    for (counter, line) in lines.iter().enumerate() {
+        let class_name = match extract_class_name(line) {
+            Some((_, name)) => name,
+            None => continue
+        };
+        let class_code_block = cut_class_block(&lines, counter);
+        let python_class = build_python_class(class_code_block.clone(), class_name, line);
+        let (file_path, class_line) = source.locate(counter + 1);
+
+        classes.push(IndexedClass::new(
+            python_class,
+            line.trim_end_matches('\r').to_string(),
+            extract_decorators(&lines, counter),
+            class_code_block,
+            file_path,
+            class_line
+        ));
+    }
+    classes
+}
+
+/// Extracts every class defined in the project
+///
+/// # Arguments
+///
+/// * `search_path` - The search roots to be indexed, in priority order
+///
+/// # Output
+///
+/// * `None` - if one of the search roots could not be read
+/// * `Some` - all the classes found, root after root in traversal order
+pub fn index_project(search_path: &SearchPath) -> Option<Vec<IndexedClass>> {
+    let roots = search_path.collect().ok()?;
+    Some(roots.iter().flat_map(|(_, sources)| sources.iter().flat_map(index_source)).collect())
+}
+
+/// Extracts every class defined in the project together with the path of
+/// its module, relative to the search root the module was found in
+///
+/// # Output
+///
+/// * `None` - if one of the search roots could not be read
+/// * `Some` - all the classes found and their module path, root after root in traversal order
+pub fn index_project_modules(search_path: &SearchPath) -> Option<Vec<(String, IndexedClass)>> {
+    let roots = search_path.collect().ok()?;
+    Some(roots.iter()
+        .flat_map(|(root, sources)| sources.iter().flat_map(move |source| {
+            let path = module_path(root, &source.path);
+            index_source(source).into_iter().map(move |indexed| (path.clone(), indexed))
+        }))
+        .collect())
+}
+
+/// The path of a module relative to its search root, or to the archive it
+/// is packed in, so the directories above the root are never matched
+pub fn module_path(root: &Path, file_path: &str) -> String {
+    match member_import_path(file_path) {
+        Some(member_path) => member_path.to_string(),
+        None => {
+            let path = Path::new(file_path);
+            path.strip_prefix(root).unwrap_or(path).to_string_lossy().to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    static PYTHON_CODE: &str = "@dataclass
+@registry.register(name='board')
+class Board(Tool):
+    \"\"\"Tic Tac Toe board\"\"\"
+    def mark(self, x: int):
+        pass
+class Marker:
+    class Meta:
+        ordering = 1
+
+    def place(self):
+        pass
+";
+
+    #[test]
+    fn test_index_source() {
+        let source = SourceFile::new(String::from("./game.py"), PYTHON_CODE.to_string());
+        let classes = index_source(&source);
+
+        let names: Vec<&str> = classes.iter().map(|indexed| indexed.class.name.as_str()).collect();
+        assert_eq!(names, vec!["Board", "Marker", "Meta"]);
+
+        assert_eq!(classes[0].location(), "./game.py:3");
+        assert_eq!(classes[0].class.inheritance, vec![String::from("Tool")]);
+        assert_eq!(classes[0].decorators, vec![
+            String::from("dataclass"),
+            String::from("registry.register(name='board')")
+        ]);
+        assert_eq!(classes[0].class.methods.len(), 1);
+        assert_eq!(classes[1].location(), "./game.py:7");
+        assert!(classes[1].decorators.is_empty());
+        assert_eq!(classes[1].class.methods.len(), 1);
+        assert_eq!(classes[2].class.methods.len(), 0);
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path(&PathBuf::from("/tmp/shop/src"), "/tmp/shop/src/billing/invoices.py"), "billing/invoices.py");
+        assert_eq!(module_path(&PathBuf::from("."), "./billing/invoices.py"), "billing/invoices.py");
+        assert_eq!(module_path(&PathBuf::from("./wheelhouse"), "./wheelhouse/shop.whl!/shop/cart.py"), "shop/cart.py");
+    }
+}
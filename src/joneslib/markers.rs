@@ -22,7 +22,7 @@ const COMMA: char = ',';
 ///
 /// * `header` - The method header
 ///
-pub fn get_header_arguments(header: &String) -> Option<String> {
+pub fn get_header_arguments(header: &str) -> Option<String> {
     let reg = Regex::new(r".*?\(|\).*").unwrap();
     let segments: Vec<&str> = reg.split(header)
         .filter(|&entry| !entry.is_empty())
@@ -0,0 +1,251 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use regex::{Regex, RegexBuilder};
+
+const EXACT_SCORE: u32 = 100;
+const PREFIX_SCORE: u32 = 80;
+const SUBSTRING_SCORE: u32 = 60;
+const FUZZY_SCORE: u32 = 40;
+
+/// Byte ranges of a text that were matched by a keyword
+pub type Spans = Vec<(usize, usize)>;
+
+/// How the keyword is compared against class names
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MatchMode {
+    Plain,
+    Regex,
+    Fuzzy
+}
+
+/// Keyword matcher used by the grep mode to find and rank class names
+#[derive(Debug)]
+pub struct Matcher {
+    keyword: String,
+    mode: MatchMode,
+    ignore_case: bool,
+    regex: Option<Regex>
+}
+impl Matcher {
+    /// Builds a matcher for the given keyword
+    ///
+    /// # Arguments
+    ///
+    /// * `keyword` - The searched keyword or pattern
+    /// * `mode` - Plain substring, regular expression or fuzzy subsequence
+    /// * `ignore_case` - Compare without taking the letter case into account
+    ///
+    /// # Errors
+    ///
+    /// Returns the regex error message if the keyword is not a valid regex
+    pub fn new(keyword: &str, mode: MatchMode, ignore_case: bool) -> Result<Self, String> {
+        let regex = match mode {
+            MatchMode::Regex => {
+                let compiled = RegexBuilder::new(keyword)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .map_err(|err| err.to_string())?;
+                Some(compiled)
+            },
+            _ => None
+        };
+
+        Ok(Matcher {
+            keyword: keyword.to_string(),
+            mode,
+            ignore_case,
+            regex
+        })
+    }
+
+    /// The keyword the matcher was built from
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    /// Counts the occurrences of the keyword inside a longer text, such as a
+    /// docstring or a module path
+    ///
+    /// Plain and fuzzy keywords are looked for as substrings regardless of the
+    /// letter case, while a regular expression counts its own matches.
+    pub fn occurrences(&self, text: &str) -> usize {
+        match &self.regex {
+            Some(regex) => regex.find_iter(text).filter(|found| found.start() != found.end()).count(),
+            None if self.keyword.is_empty() => 0,
+            None => text.to_lowercase().matches(&self.keyword.to_lowercase()).count()
+        }
+    }
+
+    /// Tries to match the text and returns the match score with the matched spans
+    ///
+    /// Exact matches score the highest, followed by prefix, substring and
+    /// finally fuzzy subsequence matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to be matched, usually a class name
+    pub fn find(&self, text: &str) -> Option<(u32, Spans)> {
+        match self.mode {
+            MatchMode::Plain => self.find_plain(text, self.ignore_case),
+            MatchMode::Regex => self.find_regex(text),
+            MatchMode::Fuzzy => self.find_plain(text, true)
+                .or_else(|| self.find_fuzzy(text))
+        }
+    }
+
+    fn find_plain(&self, text: &str, ignore_case: bool) -> Option<(u32, Spans)> {
+        let (haystack, needle) = match ignore_case {
+            true => (text.to_ascii_lowercase(), self.keyword.to_ascii_lowercase()),
+            false => (text.to_string(), self.keyword.clone())
+        };
+        if needle.is_empty() {
+            return None
+        }
+
+        let start = haystack.find(&needle)?;
+        let end = start + needle.len();
+        Some((rank_span(start, end, text.len()), vec![(start, end)]))
+    }
+
+    fn find_regex(&self, text: &str) -> Option<(u32, Spans)> {
+        let regex = self.regex.as_ref()?;
+        let found = regex.find(text)?;
+        if found.start() == found.end() {
+            return None
+        }
+        Some((rank_span(found.start(), found.end(), text.len()), vec![(found.start(), found.end())]))
+    }
+
+    /// Matches the keyword characters in order, allowing gaps between them.
+    /// Consecutive characters and characters starting a word are rewarded.
+    fn find_fuzzy(&self, text: &str) -> Option<(u32, Spans)> {
+        let mut needle = self.keyword.chars()
+            .map(|chr| chr.to_ascii_lowercase())
+            .peekable();
+        needle.peek()?;
+
+        let mut spans: Spans = Vec::new();
+        let mut bonus: u32 = 0;
+        let mut gaps: u32 = 0;
+        let mut previous: Option<char> = None;
+
+        for (pos, chr) in text.char_indices() {
+            let expected = match needle.peek() {
+                Some(expected) => *expected,
+                None => break
+            };
+            if chr.to_ascii_lowercase() != expected {
+                if !spans.is_empty() {
+                    gaps += 1;
+                }
+                previous = Some(chr);
+                continue
+            }
+            needle.next();
+
+            let word_start = match previous {
+                None => true,
+                Some(prev) => prev == '_' || (prev.is_lowercase() && chr.is_uppercase())
+            };
+            if word_start {
+                bonus += 4;
+            }
+            match spans.last_mut() {
+                Some(last) if last.1 == pos => {
+                    last.1 = pos + chr.len_utf8();
+                    bonus += 2;
+                },
+                _ => spans.push((pos, pos + chr.len_utf8()))
+            }
+            previous = Some(chr);
+        }
+
+        if needle.peek().is_some() {
+            return None
+        }
+        let score = (FUZZY_SCORE + bonus).saturating_sub(gaps.min(FUZZY_SCORE));
+        Some((score.clamp(1, SUBSTRING_SCORE - 1), spans))
+    }
+}
+
+/// Scores a contiguous match based on where it sits in the text
+fn rank_span(start: usize, end: usize, length: usize) -> u32 {
+    if start == 0 && end == length {
+        EXACT_SCORE
+    } else if start == 0 {
+        PREFIX_SCORE
+    } else {
+        SUBSTRING_SCORE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_match_is_case_sensitive(){
+        let matcher = Matcher::new("Tool", MatchMode::Plain, false).unwrap();
+
+        assert_eq!(matcher.find("ToolMind"), Some((PREFIX_SCORE, vec![(0, 4)])));
+        assert_eq!(matcher.find("toolmind"), None);
+    }
+
+    #[test]
+    fn test_plain_match_ignore_case(){
+        let matcher = Matcher::new("tool", MatchMode::Plain, true).unwrap();
+
+        assert_eq!(matcher.find("Tool"), Some((EXACT_SCORE, vec![(0, 4)])));
+        assert_eq!(matcher.find("PowerTool"), Some((SUBSTRING_SCORE, vec![(5, 9)])));
+    }
+
+    #[test]
+    fn test_regex_match(){
+        let matcher = Matcher::new(r"^Invoice\w+Service$", MatchMode::Regex, false).unwrap();
+
+        assert_eq!(matcher.find("InvoiceSyncService"), Some((EXACT_SCORE, vec![(0, 18)])));
+        assert_eq!(matcher.find("InvoiceService"), None);
+    }
+
+    #[test]
+    fn test_occurrences(){
+        let plain = Matcher::new("pay", MatchMode::Plain, false).unwrap();
+        let regex = Matcher::new(r"pay(ment|out)s?\b", MatchMode::Regex, true).unwrap();
+
+        assert_eq!(plain.occurrences("Payments are paid by the payer"), 2);
+        assert_eq!(regex.occurrences("Payments are paid by the payer"), 1);
+        assert_eq!(regex.occurrences("./billing/payouts.py"), 1);
+    }
+
+    #[test]
+    fn test_regex_invalid_pattern(){
+        assert!(Matcher::new(r"Invoice(", MatchMode::Regex, false).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_match_subsequence(){
+        let matcher = Matcher::new("invsvc", MatchMode::Fuzzy, false).unwrap();
+        let (score, spans) = matcher.find("InvoiceService").unwrap();
+
+        assert!(score < SUBSTRING_SCORE);
+        assert_eq!(spans, vec![(0, 3), (7, 8), (10, 11), (12, 13)]);
+        assert_eq!(matcher.find("ServiceInvoice"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_prefers_word_starts(){
+        let matcher = Matcher::new("is", MatchMode::Fuzzy, false).unwrap();
+        let (boundary_score, _) = matcher.find("InvoiceService").unwrap();
+        let (inner_score, _) = matcher.find("Invoicestable").unwrap();
+
+        assert!(boundary_score > inner_score);
+    }
+}
@@ -13,20 +13,49 @@ pub mod objects;
 pub mod display;
 pub mod markers;
 pub mod docstrings;
+pub mod matcher;
+pub mod sources;
+pub mod index;
+pub mod ranking;
+pub mod filters;
+pub mod docsearch;
+pub mod query;
+pub mod imports;
+pub mod project;
+pub mod hierarchy;
+pub mod diagram;
+pub mod usages;
+pub mod calls;
+pub mod functions;
+pub mod stubs;
+pub mod notebooks;
+pub mod archives;
+pub mod environments;
+pub mod revisions;
+pub mod changes;
+pub mod history;
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::cmp::Reverse;
+use objects::{ClassMatch, RankedClass, MethodMatch, TypeMatch, DocMatch, IndexedClass};
+use matcher::Matcher;
+use query::Query;
+use filters::ClassFilter;
+use sources::{SearchPath, SourceFile};
+use project::Project;
 
 const CLASS_TEMPLATE_INHERITANCE: &str = "class {template}(";
 const CLASS_TEMPLATE: &str = "class {template}:";
 const TEMPLATE_KEYWORD: &str = "{template}";
 const PYTHON_EXTENSION: &str = "py";
+const STUB_EXTENSION: &str = "pyi";
+const NOTEBOOK_EXTENSION: &str = "ipynb";
+const PACKAGE_MODULE: &str = "__init__";
 const DOCSTRING: &str = "\"\"\"";
 const NEWLINE: &str = "\n";
-const INIT_DEF: &str = "def __init__";
-
-
-type ClassMatch = (String, String);
+const FUNCTION_DEF: &str = "def ";
+const ASYNC_KEYWORD: &str = "async ";
 
 
 /// Extracts the searched python class from the code
@@ -38,33 +67,58 @@ type ClassMatch = (String, String);
 /// * `class_name`: The searched class name
 ///
 fn extract_python_class(code_lines: Vec<&str>, class_name: &str) -> objects::PythonClass {
-    let class_name_inheritance = CLASS_TEMPLATE_INHERITANCE.clone()
-        .replace(TEMPLATE_KEYWORD, class_name);
-    let class_name_simple = CLASS_TEMPLATE.clone()
-        .replace(TEMPLATE_KEYWORD, class_name);
+    let class_name_inheritance = CLASS_TEMPLATE_INHERITANCE.replace(TEMPLATE_KEYWORD, class_name);
+    let class_name_simple = CLASS_TEMPLATE.replace(TEMPLATE_KEYWORD, class_name);
+
+    let class_start = code_lines.iter()
+        .position(|line| line.contains(&class_name_inheritance) || line.contains(&class_name_simple));
+
+    let (class_header, class_code_block) = match class_start {
+        Some(start) => (code_lines[start].to_string(), cut_class_block(&code_lines, start)),
+        None => (String::new(), Vec::new())
+    };
 
-    let mut start_cutting: bool = false;
+    build_python_class(class_code_block, class_name, &class_header)
+}
+
+/// Cuts the code block of a class starting from its header line
+///
+/// The block ends at two consecutive empty lines or at the first code line
+/// that is not indented deeper than the class header.
+///
+/// # Arguments
+///
+/// * `code_lines`: The full split into lines code file
+///
+/// * `start`: The index of the class header line
+///
+fn cut_class_block(code_lines: &[&str], start: usize) -> Vec<String> {
+    let header_indent = indentation(code_lines[start]);
+    let mut header_done: bool = false;
     let mut class_code_block: Vec<String> = Vec::new();
-    let mut class_header: String = String::from("");
 
-    for (counter, line) in code_lines.iter().enumerate() {
-        if line.contains(&class_name_inheritance) || line.contains(&class_name_simple) {
-            start_cutting = true;
-            class_header.push_str(line)
+    for (counter, line) in code_lines.iter().enumerate().skip(start) {
+        let is_blank = line.trim().is_empty();
+        if header_done && !is_blank && indentation(line) <= header_indent {
+            break
+        }
+        if !header_done && (line.trim_end().ends_with(':') || utils::header_end(line).is_some()) {
+            header_done = true;
         }
-        if start_cutting {
-            class_code_block.push(line.to_string());
+        class_code_block.push(line.to_string());
 
-            if line.len() <= 1 && code_lines[counter-1].len() <= 1 {
-                break
-            }
+        if counter > start && line.len() <= 1 && code_lines[counter-1].len() <= 1 {
+            break
         }
     }
 
-    let class_inheritance = match utils::extract_class_inheritance(&class_header) {
-        Some(inheritance_vec) => inheritance_vec,
-        None => Vec::new()
-    };
+    class_code_block
+}
+
+/// Builds a PythonClass out of its code block and header line
+fn build_python_class(class_code_block: Vec<String>, class_name: &str, class_header: &str) -> objects::PythonClass {
+    let class_inheritance = utils::extract_class_inheritance(class_header)
+        .unwrap_or_default();
 
     let docstring = match docstrings::extract_docstring(&class_code_block) {
         Some(inheritance_vec) => inheritance_vec,
@@ -74,6 +128,29 @@ fn extract_python_class(code_lines: Vec<&str>, class_name: &str) -> objects::Pyt
     objects::PythonClass::new(class_code_block, class_name.to_string(), class_inheritance, docstring)
 }
 
+/// Checks if the file is a Python module or a `.pyi` stub
+fn is_python_file(file_path: &Path) -> bool {
+    match file_path.extension() {
+        Some(extension) => extension == PYTHON_EXTENSION || extension == STUB_EXTENSION,
+        None => false
+    }
+}
+
+/// Checks if the file is a `.pyi` stub
+fn is_stub_file(file_path: &Path) -> bool {
+    file_path.extension().map(|extension| extension == STUB_EXTENSION).unwrap_or(false)
+}
+
+/// Checks if the file is a Jupyter notebook
+fn is_notebook_file(file_path: &Path) -> bool {
+    file_path.extension().map(|extension| extension == NOTEBOOK_EXTENSION).unwrap_or(false)
+}
+
+/// Number of whitespace characters a code line is indented with
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
 /// Check if a file contains the searched class by reading the file.
 ///
 /// # Arguments
@@ -83,115 +160,187 @@ fn extract_python_class(code_lines: Vec<&str>, class_name: &str) -> objects::Pyt
 /// # Errors
 /// It panics if the file is cannot be read properly
 fn check_file_contains_class(class_name: &str, file_path: &str) -> bool {
-    let class_name_inheritance = CLASS_TEMPLATE_INHERITANCE.clone()
-        .replace(TEMPLATE_KEYWORD, class_name);
-    let class_name = CLASS_TEMPLATE.clone()
-        .replace(TEMPLATE_KEYWORD, class_name);
+    let class_name_inheritance = CLASS_TEMPLATE_INHERITANCE.replace(TEMPLATE_KEYWORD, class_name);
+    let class_name = CLASS_TEMPLATE.replace(TEMPLATE_KEYWORD, class_name);
 
     match fs::read_to_string(file_path) {
         Ok(file_content) => {
             let first_check = file_content.contains(&class_name_inheritance);
             let second_check = file_content.contains(&class_name);
-            return first_check || second_check
+            first_check || second_check
         },
-        Err(_) => {
-            return false
-        }
-    };
+        Err(_) => false
+    }
 }
 
 /// Searches recurssively through a project for a Python class and extracts that
 /// class into an PythonClass struct.
-pub fn project_traversal(dir_path: &PathBuf, class_name: &String) -> Option<objects::PythonClass> {
-    let current_dir = match fs::read_dir(dir_path) {
-        Ok(dir) => dir,
-        Err(err) => {
-            println!("Error occured while reading dir: {}", err);
-            return None
-        }
-    };
+pub fn project_traversal(dir_path: &Path, class_name: &str) -> Option<objects::PythonClass> {
+    let file_path = find_class_file(dir_path, class_name)?;
+    let file_content = fs::read_to_string(file_path).ok()?;
+    let lines: Vec<&str> = file_content.split('\n').collect();
 
-    for file in current_dir {
-        let file_path = file.unwrap().path();
-        let file_path_name = file_path.to_str().unwrap();
+    Some(extract_python_class(lines, class_name))
+}
+
+/// Searches recursively through a directory for the first Python file
+/// defining a class, without reading the files after it
+fn find_class_file(dir_path: &Path, class_name: &str) -> Option<PathBuf> {
+    for file in fs::read_dir(dir_path).ok()? {
+        let file_path = match file {
+            Ok(entry) => entry.path(),
+            Err(_) => continue
+        };
         if file_path.is_dir() {
-            match project_traversal(&file_path, class_name) {
-                Some(value) => {
-                   return Some(value)
-                },
+            match find_class_file(&file_path, class_name) {
+                Some(found) => return Some(found),
                 None => continue
-            };
-        } else {
-            match file_path.extension() {
-                Some(extension) => {
-                    if extension != PYTHON_EXTENSION {
-                        continue
-                    }
-                },
-                None => continue
-            }
-            if check_file_contains_class(class_name, &file_path_name){
-                let file_content = match fs::read_to_string(file_path) {
-                    Ok(content) => content,
-                    Err(_) => {
-                        println!("Now skipping");
-                        continue
-                    }
-                };
-                let lines: Vec<&str> = file_content.split("\n").collect();
-
-                return Some(extract_python_class(lines, class_name))
             }
         }
+        if is_python_file(&file_path) && check_file_contains_class(class_name, &file_path.to_string_lossy()) {
+            return Some(file_path)
+        }
     }
-    return None
+    None
 }
 
-/// Project traversal recursive and searches for a keyword based on itself or on context (Phase 2)
-pub fn smart_search(dir_path: &PathBuf, class_name: &String)  -> Option<Vec<ClassMatch>>{
-    let mut found_matched_classes: Vec<ClassMatch> = Vec::new();
+/// Looks a class up in the search roots, stopping at the first file defining
+/// it, and indexes only the modules its import path goes through
+///
+/// # Output
+///
+/// * `None` - if the class is not defined in a Python file of the working
+///   directory, in which case the whole project has to be indexed
+/// * `Some` - the indexed modules with the position of the class
+pub fn lookup_class(search_path: &SearchPath, class_name: &str) -> Option<(Project, usize)> {
+    if search_path.revision.is_some() {
+        return None
+    }
+    let simple_name = class_name.rsplit('.').next().unwrap_or(class_name);
+    let (root, file_path) = search_path.roots.iter()
+        .find_map(|root| Some((root, find_class_file(root, simple_name)?)))?;
 
-    let current_dir = match fs::read_dir(dir_path) {
-        Ok(dir) => dir,
-        Err(err) => {
-            println!("Error occured while reading dir: {}", err);
-            return None
-        }
-    };
+    let project = Project::with_roots(vec![(root.clone(), import_sources(root, &file_path))]);
+    let class_id = project.find_class(class_name)?;
+    Some((project, class_id))
+}
 
-    for file in current_dir {
-        let file_path = file.unwrap().path();
-        let file_path_name = file_path.to_str().unwrap();
-        if file_path.is_dir() {
-            match smart_search(&file_path, class_name) {
-                Some(matches) => found_matched_classes.extend(matches),
-                None => continue
-            };
-        } else {
-            match file_path.extension() {
-                Some(extension) => {
-                    if extension != PYTHON_EXTENSION {
-                        continue
-                    }
-                },
-                None => continue
-            };
-            let file_content = match fs::read_to_string(&file_path) {
-                Ok(content) => content,
-                Err(_) => continue
-            };
-            let lines: Vec<&str> = file_content.split("\n").collect();
-            match utils::grep_class(lines, &class_name, file_path_name) {
-                Some(matches) => found_matched_classes.extend(matches),
-                None => continue
+/// The module of a file together with its stub and the `__init__` modules of
+/// the packages above it, up to the search root
+fn import_sources(root: &Path, file_path: &Path) -> Vec<SourceFile> {
+    let mut paths: Vec<PathBuf> = vec![file_path.with_extension(PYTHON_EXTENSION), file_path.with_extension(STUB_EXTENSION)];
+    let mut package = file_path.parent();
+    while let Some(dir_path) = package.filter(|dir_path| dir_path.starts_with(root) && *dir_path != root) {
+        let package_file = dir_path.join(PACKAGE_MODULE);
+        for extension in [PYTHON_EXTENSION, STUB_EXTENSION] {
+            let path = package_file.with_extension(extension);
+            if !paths.contains(&path) {
+                paths.push(path);
             }
         }
+        package = dir_path.parent();
+    }
+
+    paths.iter()
+        .filter_map(|path| Some(SourceFile::new(path.to_string_lossy().to_string(), fs::read_to_string(path).ok()?)))
+        .collect()
+}
+
+/// Project traversal recursive and searches for classes whose name matches the keyword
+///
+/// Only the classes passing the filter are kept and they are ranked by how
+/// well their name matches the keyword.
+pub fn grep_search(search_path: &SearchPath, matcher: &Matcher, filter: &ClassFilter) -> Option<Vec<ClassMatch>> {
+    let mut found_matched_classes: Vec<ClassMatch> = index::index_project_modules(search_path)?
+        .iter()
+        .filter(|(module_path, indexed)| filter.matches(indexed, module_path))
+        .filter_map(|(_, indexed)| utils::grep_class(indexed, matcher))
+        .collect();
+    found_matched_classes.sort_by_key(|class_match| Reverse(class_match.score));
+
+    match found_matched_classes.is_empty() {
+        true => None,
+        false => Some(found_matched_classes)
+    }
+}
+
+/// Project traversal recursive and evaluates a query over every class in the project
+///
+/// The classes satisfying the query are returned in traversal order.
+pub fn query_search(search_path: &SearchPath, query: &Query) -> Option<Vec<IndexedClass>> {
+    let found_classes: Vec<IndexedClass> = index::index_project_modules(search_path)?
+        .into_iter()
+        .filter(|(module_path, indexed)| query.matches(indexed, module_path))
+        .map(|(_, indexed)| indexed)
+        .collect();
+
+    match found_classes.is_empty() {
+        true => None,
+        false => Some(found_classes)
+    }
+}
+
+/// Project traversal recursive and searches for a keyword based on itself or on context
+///
+/// Every class in the project is scored by its name, docstring, method names,
+/// base classes and module name and the related ones are returned by relevance.
+pub fn smart_search(search_path: &SearchPath, matcher: &Matcher) -> Option<Vec<RankedClass>> {
+    let mut ranked_classes: Vec<RankedClass> = index::index_project_modules(search_path)?
+        .into_iter()
+        .filter_map(|(module_path, indexed)| ranking::rank_class(indexed, &module_path, matcher))
+        .collect();
+    ranked_classes.sort_by_key(|ranked| Reverse(ranked.score));
+
+    match ranked_classes.is_empty() {
+        true => None,
+        false => Some(ranked_classes)
     }
+}
 
-    if found_matched_classes.len() > 0 {
-        Some(found_matched_classes)
-    } else {
-        None
+/// Project traversal recursive and searches for methods whose name matches the keyword
+///
+/// Every class defining a matched method is reported, ranked by how well the
+/// method name matches the keyword.
+pub fn method_search(search_path: &SearchPath, matcher: &Matcher) -> Option<Vec<MethodMatch>> {
+    let mut found_methods: Vec<MethodMatch> = index::index_project(search_path)?
+        .into_iter()
+        .flat_map(|indexed| utils::grep_methods(indexed, matcher))
+        .collect();
+    found_methods.sort_by_key(|method_match| Reverse(method_match.score));
+
+    match found_methods.is_empty() {
+        true => None,
+        false => Some(found_methods)
+    }
+}
+
+/// Project traversal recursive and searches for methods accepting or returning a type
+pub fn type_search(search_path: &SearchPath, type_name: &str, ignore_case: bool) -> Option<Vec<TypeMatch>> {
+    let found_types: Vec<TypeMatch> = index::index_project(search_path)?
+        .into_iter()
+        .flat_map(|indexed| utils::grep_types(indexed, type_name, ignore_case))
+        .collect();
+
+    match found_types.is_empty() {
+        true => None,
+        false => Some(found_types)
+    }
+}
+
+/// Project traversal recursive and searches for a text inside class and method docstrings
+///
+/// The docstrings with the most hits are returned first.
+pub fn doc_search(search_path: &SearchPath, query: &str) -> Option<Vec<DocMatch>> {
+    let query = docsearch::DocQuery::parse(query);
+    let mut found_docs: Vec<DocMatch> = index::index_project(search_path)?
+        .iter()
+        .flat_map(|indexed| docsearch::search_class(indexed, &query))
+        .collect();
+    found_docs.sort_by_key(|doc_match| Reverse(doc_match.score));
+
+    match found_docs.is_empty() {
+        true => None,
+        false => Some(found_docs)
     }
 }
 
@@ -202,6 +351,8 @@ mod tests {
     use super::extract_python_class;
     use super::project_traversal;
     use super::check_file_contains_class;
+    use super::lookup_class;
+    use super::sources::SearchPath;
     use std::fs;
     use std::path::PathBuf;
 
@@ -239,7 +390,7 @@ mod tests {
             "        print(f'My name is {self.name}')".to_string(),
             "".to_string()
         ];
-        let lines: Vec<&str> = PYTHON_CODE.split("\n").collect();
+        let lines: Vec<&str> = PYTHON_CODE.split('\n').collect();
 
         let expected_class = PythonClass::new(test_codebase, String::from("God"), Vec::new(), String::from("DocString"));
 
@@ -252,13 +403,13 @@ mod tests {
         let path = "./test.py";
         fs::write(path, PYTHON_CODE).unwrap();
 
-        assert_eq!(true, check_file_contains_class("God", path));
+        assert!(check_file_contains_class("God", path));
         fs::remove_file(path).unwrap();
     }
 
     #[test]
     fn test_check_file_contains_class_err() {
-        assert_eq!(check_file_contains_class("God", "./iogh.py"), false);
+        assert!(!check_file_contains_class("God", "./iogh.py"));
     }
 
     #[test]
@@ -283,7 +434,7 @@ mod tests {
         ];
 
         let expected_class = PythonClass::new(test_codebase, String::from("God"), Vec::new(), String::from("DocString"));
-        assert_eq!(expected_class, project_traversal(&pathbuf, &"God".to_string()).unwrap());
+        assert_eq!(expected_class, project_traversal(&pathbuf, "God").unwrap());
 
         fs::remove_dir_all("./testing").expect("Could not delete dir");
     }
@@ -304,9 +455,33 @@ mod tests {
         pathbuf.push("./testing_none");
 
         // Assert
-        assert_eq!( project_traversal(&pathbuf, &"TestCode".to_string()), None);
+        assert_eq!( project_traversal(&pathbuf, "TestCode"), None);
 
         // Destroy the test dir
         fs::remove_dir_all("./testing_none").expect("Could not delete dir");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_lookup_class() {
+        let test_dir = "./testing_lookup";
+        fs::create_dir_all(format!("{}/pkg", test_dir)).expect("Could not write dir");
+        fs::create_dir_all(format!("{}/other", test_dir)).expect("Could not write dir");
+        fs::write(format!("{}/pkg/__init__.py", test_dir), "from .workers import Worker\n").unwrap();
+        fs::write(format!("{}/pkg/workers.py", test_dir), "class Worker:\n    async def run(self):\n        pass\n").unwrap();
+        fs::write(format!("{}/pkg/workers.pyi", test_dir), "class Worker:\n    async def run(self) -> None: ...\n").unwrap();
+        fs::write(format!("{}/other/jobs.py", test_dir), "class Job:\n    pass\n").unwrap();
+
+        let search_path = SearchPath::new(vec![PathBuf::from(test_dir)], None, false);
+        let found = lookup_class(&search_path, "Worker");
+        let missing = lookup_class(&search_path, "Missing");
+        let at_revision = lookup_class(&SearchPath::new(vec![PathBuf::from(test_dir)], Some(String::from("HEAD")), false), "Worker");
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        let (project, class_id) = found.unwrap();
+        assert_eq!(project.modules.len(), 3);
+        assert_eq!(project.import_path(class_id), "from pkg import Worker");
+        assert_eq!(project.classes[class_id].class.methods[0].output, "None");
+        assert!(missing.is_none());
+        assert!(at_revision.is_none());
+    }
+}
@@ -0,0 +1,102 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use serde_json::Value;
+use super::sources::{Cell, SourceFile};
+
+const CODE_CELL: &str = "code";
+const MAGIC_PREFIXES: [char; 2] = ['%', '!'];
+
+/// Reads a Jupyter notebook as a Python source, by joining its code cells
+///
+/// The IPython magics and shell escapes (`%timeit`, `!pip install`) are
+/// blanked, so the lines keep their position inside the cell.
+///
+/// # Arguments
+///
+/// * `path` - The path of the `.ipynb` file
+/// * `content` - The notebook JSON
+///
+/// # Output
+///
+/// * `None` - if the content is not a notebook
+/// * `Some` - the code cells joined, remembering where each one starts
+pub fn read_notebook(path: String, content: &str) -> Option<SourceFile> {
+    let notebook: Value = serde_json::from_str(content).ok()?;
+    let mut code = String::new();
+    let mut cells: Vec<Cell> = Vec::new();
+    let mut next_line: usize = 1;
+
+    for (position, cell) in notebook.get("cells")?.as_array()?.iter().enumerate() {
+        if cell.get("cell_type").and_then(Value::as_str) != Some(CODE_CELL) {
+            continue
+        }
+        let cell_source = match cell.get("source") {
+            Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect::<String>(),
+            Some(Value::String(text)) => text.clone(),
+            _ => continue
+        };
+
+        cells.push(Cell::new(position + 1, next_line));
+        for line in cell_source.trim_end_matches('\n').split('\n') {
+            if !line.trim_start().starts_with(MAGIC_PREFIXES) {
+                code.push_str(line);
+            }
+            code.push('\n');
+            next_line += 1;
+        }
+    }
+
+    Some(SourceFile::notebook(path, code, cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static NOTEBOOK: &str = r##"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Churn model\n"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["%matplotlib inline\n", "import pandas as pd"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": [
+   "class Model:\n",
+   "    \"\"\"Predicts the churn\"\"\"\n",
+   "    def fit(self, frame: pd.DataFrame) -> None:\n",
+   "        pass\n"
+  ]}
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_read_notebook() {
+        let source = read_notebook(String::from("./churn.ipynb"), NOTEBOOK).unwrap();
+
+        assert_eq!(source.lines(), vec![
+            "",
+            "import pandas as pd",
+            "class Model:",
+            "    \"\"\"Predicts the churn\"\"\"",
+            "    def fit(self, frame: pd.DataFrame) -> None:",
+            "        pass",
+            ""
+        ]);
+        assert_eq!(source.locate(2), (String::from("./churn.ipynb:cell_2"), 2));
+        assert_eq!(source.locate(5), (String::from("./churn.ipynb:cell_3"), 3));
+    }
+
+    #[test]
+    fn test_read_notebook_invalid() {
+        assert_eq!(read_notebook(String::from("./broken.ipynb"), "{\"cells\": "), None);
+        assert_eq!(read_notebook(String::from("./empty.ipynb"), "{}"), None);
+    }
+}
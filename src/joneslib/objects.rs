@@ -8,7 +8,8 @@ License: MIT
 Copyright 2021 Vlad Nedelcu
 */
 
-use super::utils;
+use super::{utils, indentation};
+use super::matcher::Spans;
 use std::fmt;
 use ansi_term::Colour;
 
@@ -44,13 +45,10 @@ pub struct Method{
     pub output: String
 }
 impl Method {
-    pub fn new(method_header: &String) -> Self {
+    pub fn new(method_header: &str) -> Self {
         let method_name = match utils::extract_method_name(method_header) {
             Ok(name) => name,
-            Err(err) => {
-                println!("Error while extracting method name: {}", err);
-                String::from("ENL")
-            }
+            Err(_) => String::from("ENL")
         };
         let method_output = match utils::extract_method_output(method_header) {
             Ok(output) => output,
@@ -62,6 +60,17 @@ impl Method {
             parameters: utils::extract_parameters(method_header)
         }
     }
+
+    /// The method signature as written in Python, without colours
+    pub fn signature(&self) -> String {
+        let parameters: Vec<String> = self.parameters.iter()
+            .map(|parameter| match parameter.static_type.as_str() {
+                "None" => parameter.name.clone(),
+                static_type => format!("{}: {}", parameter.name, static_type)
+            })
+            .collect();
+        format!("{}({}) -> {}", self.name, parameters.join(", "), self.output)
+    }
 }
 impl fmt::Display for Method{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -83,7 +92,7 @@ pub struct PythonClass{
 impl PythonClass {
     pub fn new(class_code: Vec<String>, name: String, inheritance: Vec<String>, docstring: String) -> Self {
         PythonClass {
-            name: name,
+            name,
             methods: utils::extract_methods(class_code),
             inheritance,
             docstring
@@ -93,10 +102,561 @@ impl PythonClass {
 impl fmt::Display for PythonClass{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let inheritance_display = self.inheritance.join(", ");
-        write!(f, "# Class :: [{}]\n{}\n* inherit -> {}\n\n# Methods\n-------",
+        write!(f, "# Class :: [{}]\n{}\n* inherit -> {}",
             Colour::Cyan.paint(&self.name),
             Colour::Yellow.paint(&self.docstring),
             Colour::Green.paint(inheritance_display)
         )
     }
-}
\ No newline at end of file
+}
+
+/// A class header found by the grep mode together with its ranking
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct ClassMatch{
+    pub header: String,
+    pub file_path: String,
+    pub score: u32,
+    pub spans: Spans
+}
+impl ClassMatch {
+    pub fn new(header: String, file_path: String, score: u32, spans: Spans) -> Self {
+        ClassMatch {
+            header,
+            file_path,
+            score,
+            spans
+        }
+    }
+}
+
+/// A Python class found in the project together with where it is defined
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct IndexedClass{
+    pub class: PythonClass,
+    pub header: String,
+    pub decorators: Vec<String>,
+    pub code: Vec<String>,
+    pub file_path: String,
+    pub line: usize,
+    pub stub: Option<StubMerge>
+}
+impl IndexedClass {
+    pub fn new(class: PythonClass, header: String, decorators: Vec<String>, code: Vec<String>, file_path: String, line: usize) -> Self {
+        IndexedClass {
+            class,
+            header,
+            decorators,
+            code,
+            file_path,
+            line,
+            stub: None
+        }
+    }
+
+    /// If the signature of the method was taken from the `.pyi` stub of the class
+    pub fn is_stub_method(&self, method_name: &str) -> bool {
+        self.stub.as_ref()
+            .map(|stub| stub.methods.iter().any(|name| name == method_name))
+            .unwrap_or(false)
+    }
+
+    /// The file and line where the class is defined
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file_path, self.line)
+    }
+
+    /// The file and line where a method of the class is defined
+    ///
+    /// Falls back to the class location if the method header is not found
+    pub fn method_location(&self, method_name: &str) -> String {
+        format!("{}:{}", self.file_path, self.method_line(method_name))
+    }
+
+    /// The line where a method of the class is defined, or the class line
+    /// if the method header is not found
+    pub fn method_line(&self, method_name: &str) -> usize {
+        self.line + self.method_start(method_name).unwrap_or(0)
+    }
+
+    /// The code block of a method, from its header to the end of its body
+    pub fn method_code(&self, method_name: &str) -> Vec<String> {
+        let start = match self.method_start(method_name) {
+            Some(start) => start,
+            None => return Vec::new()
+        };
+        let method_indent = indentation(&self.code[start]);
+        let mut header_done = false;
+        let mut method_code: Vec<String> = Vec::new();
+
+        for line in self.code[start..].iter() {
+            if header_done && !line.trim().is_empty() && indentation(line) <= method_indent {
+                break
+            }
+            if !header_done && (line.trim_end().ends_with(':') || utils::header_end(line).is_some()) {
+                header_done = true;
+            }
+            method_code.push(line.clone());
+        }
+        method_code
+    }
+
+    fn method_start(&self, method_name: &str) -> Option<usize> {
+        let method_header = format!("def {}(", method_name);
+        self.code.iter()
+            .position(|line| line.trim_start()
+                .trim_start_matches("async ")
+                .starts_with(&method_header))
+    }
+}
+
+/// The pieces of a class taken from its `.pyi` stub when both the stub and
+/// the implementation define the class
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct StubMerge{
+    pub location: String,
+    pub methods: Vec<String>,
+    pub docstring: bool
+}
+impl StubMerge {
+    pub fn new(location: String, methods: Vec<String>, docstring: bool) -> Self {
+        StubMerge {
+            location,
+            methods,
+            docstring
+        }
+    }
+}
+
+/// A class found by the smart search with its relevance score and the
+/// parts of the class that matched the keyword
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct RankedClass{
+    pub indexed: IndexedClass,
+    pub score: u32,
+    pub hits: Vec<String>
+}
+impl RankedClass {
+    pub fn new(indexed: IndexedClass, score: u32, hits: Vec<String>) -> Self {
+        RankedClass {
+            indexed,
+            score,
+            hits
+        }
+    }
+}
+
+/// A method found by the method search together with the class defining it
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct MethodMatch{
+    pub class_name: String,
+    pub method: Method,
+    pub location: String,
+    pub score: u32,
+    pub spans: Spans
+}
+impl MethodMatch {
+    pub fn new(class_name: String, method: Method, location: String, score: u32, spans: Spans) -> Self {
+        MethodMatch {
+            class_name,
+            method,
+            location,
+            score,
+            spans
+        }
+    }
+}
+
+/// A method parameter or return value annotated with the searched type
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct TypeMatch{
+    pub class_name: String,
+    pub method_name: String,
+    pub parameter: Option<Parameter>,
+    pub output: String,
+    pub location: String
+}
+impl TypeMatch {
+    pub fn new(class_name: String, method_name: String, parameter: Option<Parameter>, output: String, location: String) -> Self {
+        TypeMatch {
+            class_name,
+            method_name,
+            parameter,
+            output,
+            location
+        }
+    }
+
+    /// The matched usage written as `Class.method(param: Type)` or `Class.method() -> Type`
+    pub fn usage(&self) -> String {
+        match &self.parameter {
+            Some(parameter) => format!("{}.{}({}: {})",
+                self.class_name, self.method_name, parameter.name, parameter.static_type),
+            None => format!("{}.{}() -> {}", self.class_name, self.method_name, self.output)
+        }
+    }
+}
+
+/// A class or method docstring containing the searched text
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct DocMatch{
+    pub class_name: String,
+    pub method_name: Option<String>,
+    pub excerpt: String,
+    pub spans: Spans,
+    pub location: String,
+    pub score: u32
+}
+impl DocMatch {
+    pub fn new(class_name: String, method_name: Option<String>, excerpt: String, spans: Spans, location: String, score: u32) -> Self {
+        DocMatch {
+            class_name,
+            method_name,
+            excerpt,
+            spans,
+            location,
+            score
+        }
+    }
+}
+
+/// A name bound by an import statement at module level
+///
+/// `from billing.base import Base as B` binds the alias `B` to the name
+/// `Base` of the module `billing.base`, while `import billing.base` binds
+/// the alias `billing.base` to the module itself.
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Import{
+    pub module: String,
+    pub name: Option<String>,
+    pub alias: String,
+    pub line: usize
+}
+impl Import {
+    pub fn new(module: String, name: Option<String>, alias: String, line: usize) -> Self {
+        Import {
+            module,
+            name,
+            alias,
+            line
+        }
+    }
+
+    /// The import written as an absolute Python statement
+    pub fn statement(&self) -> String {
+        let imported = match &self.name {
+            Some(name) => format!("from {} import {}", self.module, name),
+            None => format!("import {}", self.module)
+        };
+        let bound = self.name.as_deref().unwrap_or(&self.module);
+        match self.alias == bound {
+            true => imported,
+            false => format!("{} as {}", imported, self.alias)
+        }
+    }
+}
+
+/// A base class of a class, resolved to its definition inside the project
+///
+/// Bases defined outside of the project, in the standard library or in a
+/// third party package, have no location and no parents.
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Ancestor{
+    pub written: String,
+    pub class_name: String,
+    pub location: Option<String>,
+    pub parents: Vec<Ancestor>
+}
+impl Ancestor {
+    pub fn new(written: String, class_name: String, location: Option<String>, parents: Vec<Ancestor>) -> Self {
+        Ancestor {
+            written,
+            class_name,
+            location,
+            parents
+        }
+    }
+
+    /// If the base is defined outside of the project
+    pub fn is_external(&self) -> bool {
+        self.location.is_none()
+    }
+}
+
+/// A class inheriting, directly or through its children, the inspected class
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Subclass{
+    pub class_name: String,
+    pub location: String,
+    pub children: Vec<Subclass>
+}
+impl Subclass {
+    pub fn new(class_name: String, location: String, children: Vec<Subclass>) -> Self {
+        Subclass {
+            class_name,
+            location,
+            children
+        }
+    }
+}
+
+/// The way a class is used by a line of code
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsageKind {
+    Import,
+    Instantiation,
+    InstanceCheck,
+    Annotation,
+    Subclass,
+    Reference
+}
+impl fmt::Display for UsageKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            UsageKind::Import => "Imports",
+            UsageKind::Instantiation => "Instantiations",
+            UsageKind::InstanceCheck => "Instance checks",
+            UsageKind::Annotation => "Type annotations",
+            UsageKind::Subclass => "Subclasses",
+            UsageKind::Reference => "Other references"
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A line of the project using a class, directly or through an alias
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Usage{
+    pub kind: UsageKind,
+    pub code: String,
+    pub location: String
+}
+impl Usage {
+    pub fn new(kind: UsageKind, code: String, location: String) -> Self {
+        Usage {
+            kind,
+            code,
+            location
+        }
+    }
+}
+
+/// A function defined at the module level of a Python file
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct IndexedFunction{
+    pub function: Method,
+    pub decorators: Vec<String>,
+    pub docstring: Option<String>,
+    pub file_path: String,
+    pub line: usize
+}
+impl IndexedFunction {
+    pub fn new(function: Method, decorators: Vec<String>, docstring: Option<String>, file_path: String, line: usize) -> Self {
+        IndexedFunction {
+            function,
+            decorators,
+            docstring,
+            file_path,
+            line
+        }
+    }
+
+    /// The file and line where the function is defined
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file_path, self.line)
+    }
+}
+
+/// A constant assigned at the module level of a Python file, with its
+/// annotation as the static type
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Constant{
+    pub constant: Parameter,
+    pub value: String,
+    pub file_path: String,
+    pub line: usize
+}
+impl Constant {
+    pub fn new(constant: Parameter, value: String, file_path: String, line: usize) -> Self {
+        Constant {
+            constant,
+            value,
+            file_path,
+            line
+        }
+    }
+
+    /// The file and line where the constant is assigned
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file_path, self.line)
+    }
+}
+
+/// A change of the public API of a class between two versions of it
+#[derive(Debug, Clone)]
+#[derive(PartialEq)]
+pub enum ApiChange {
+    ClassAdded { class_name: String },
+    ClassRemoved { class_name: String },
+    BasesChanged { from: Vec<String>, to: Vec<String> },
+    MethodAdded { signature: String },
+    MethodRemoved { signature: String },
+    ParameterAdded { method: String, parameter: String, required: bool },
+    ParameterRemoved { method: String, parameter: String },
+    ParameterRenamed { method: String, from: String, to: String },
+    ParametersReordered { method: String, from: Vec<String>, to: Vec<String> },
+    TypeChanged { method: String, parameter: String, from: String, to: String },
+    DefaultChanged { method: String, parameter: String, from: Option<String>, to: Option<String> },
+    ReturnTypeChanged { method: String, from: String, to: String }
+}
+impl ApiChange {
+    /// `+` for the added members, `-` for the removed ones and `~` for the changed ones
+    pub fn marker(&self) -> char {
+        match self {
+            ApiChange::ClassAdded { .. } | ApiChange::MethodAdded { .. } | ApiChange::ParameterAdded { .. } => '+',
+            ApiChange::ClassRemoved { .. } | ApiChange::MethodRemoved { .. } | ApiChange::ParameterRemoved { .. } => '-',
+            _ => '~'
+        }
+    }
+
+    /// If the change breaks the code calling the old version: removed classes,
+    /// methods, parameters and bases, new required parameters, renamed or
+    /// reordered parameters and removed default values. Annotations are not
+    /// checked at runtime, so their changes are compatible.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            ApiChange::ClassRemoved { .. } | ApiChange::MethodRemoved { .. } | ApiChange::ParameterRemoved { .. } => true,
+            ApiChange::ParameterRenamed { .. } | ApiChange::ParametersReordered { .. } => true,
+            ApiChange::ParameterAdded { required, .. } => *required,
+            ApiChange::DefaultChanged { from, to, .. } => from.is_some() && to.is_none(),
+            ApiChange::BasesChanged { from, to } => from.iter().any(|base| !to.contains(base)),
+            _ => false
+        }
+    }
+
+    /// The kind of the change in snake case, used by the machine readable reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiChange::ClassAdded { .. } => "class_added",
+            ApiChange::ClassRemoved { .. } => "class_removed",
+            ApiChange::BasesChanged { .. } => "bases_changed",
+            ApiChange::MethodAdded { .. } => "method_added",
+            ApiChange::MethodRemoved { .. } => "method_removed",
+            ApiChange::ParameterAdded { .. } => "parameter_added",
+            ApiChange::ParameterRemoved { .. } => "parameter_removed",
+            ApiChange::ParameterRenamed { .. } => "parameter_renamed",
+            ApiChange::ParametersReordered { .. } => "parameters_reordered",
+            ApiChange::TypeChanged { .. } => "type_changed",
+            ApiChange::DefaultChanged { .. } => "default_changed",
+            ApiChange::ReturnTypeChanged { .. } => "return_type_changed"
+        }
+    }
+}
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let written = |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("no default"));
+        match self {
+            ApiChange::ClassAdded { class_name } => write!(f, "class {}", class_name),
+            ApiChange::ClassRemoved { class_name } => write!(f, "class {}", class_name),
+            ApiChange::BasesChanged { from, to } =>
+                write!(f, "bases: ({}) -> ({})", from.join(", "), to.join(", ")),
+            ApiChange::MethodAdded { signature } => write!(f, "method {}", signature),
+            ApiChange::MethodRemoved { signature } => write!(f, "method {}", signature),
+            ApiChange::ParameterAdded { method, parameter, .. } => write!(f, "{}: parameter {}", method, parameter),
+            ApiChange::ParameterRemoved { method, parameter } => write!(f, "{}: parameter {}", method, parameter),
+            ApiChange::ParameterRenamed { method, from, to } =>
+                write!(f, "{}: parameter {} renamed to {}", method, from, to),
+            ApiChange::ParametersReordered { method, from, to } =>
+                write!(f, "{}: parameters reordered ({}) -> ({})", method, from.join(", "), to.join(", ")),
+            ApiChange::TypeChanged { method, parameter, from, to } =>
+                write!(f, "{}: parameter {} type {} -> {}", method, parameter, from, to),
+            ApiChange::DefaultChanged { method, parameter, from, to } =>
+                write!(f, "{}: parameter {} default {} -> {}", method, parameter, written(from), written(to)),
+            ApiChange::ReturnTypeChanged { method, from, to } =>
+                write!(f, "{}: return type {} -> {}", method, from, to)
+        }
+    }
+}
+
+/// The API changes of a class, found by its public import path
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct ClassChanges {
+    pub class_name: String,
+    pub changes: Vec<ApiChange>
+}
+impl ClassChanges {
+    pub fn new(class_name: String, changes: Vec<ApiChange>) -> Self {
+        ClassChanges {
+            class_name,
+            changes
+        }
+    }
+}
+
+/// A commit of the git log, as shown in the class history
+#[derive(Debug, Clone)]
+#[derive(PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub summary: String
+}
+impl Commit {
+    pub fn new(hash: String, author: String, date: String, summary: String) -> Self {
+        Commit {
+            hash,
+            author,
+            date,
+            summary
+        }
+    }
+
+    /// The abbreviated hash, as printed by `git log --oneline`
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+impl fmt::Display for Commit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}: {}", self.short_hash(), self.date, self.author, self.summary)
+    }
+}
+
+/// When a method of a class was introduced and when its signature last changed
+///
+/// A method without an introducing commit exists only in the working directory.
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct MethodHistory {
+    pub name: String,
+    pub signature: String,
+    pub introduced: Option<Commit>,
+    pub changed: Option<Commit>,
+    pub uncommitted: bool
+}
+impl MethodHistory {
+    pub fn new(name: String, signature: String, introduced: Option<Commit>) -> Self {
+        MethodHistory {
+            name,
+            signature,
+            uncommitted: introduced.is_none(),
+            introduced,
+            changed: None
+        }
+    }
+}
@@ -0,0 +1,706 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use super::{
+    index::{index_source, module_path},
+    functions::{extract_constants, index_functions},
+    imports::{extract_exports, extract_imports, module_name},
+    objects::{Ancestor, Constant, Import, IndexedClass, IndexedFunction, Subclass},
+    sources::{locate_line, Cell, SearchPath, SourceFile},
+    stubs::merge_stub,
+    is_stub_file
+};
+
+const MAX_RESOLVE_DEPTH: usize = 16;
+const PACKAGE_FILE: &str = "__init__";
+const STAR_IMPORT: &str = "*";
+const PYTHON_FILE_EXTENSION: &str = ".py";
+
+/// A module of the project with the names it imports
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub file_path: String,
+    pub is_package: bool,
+    pub imports: Vec<Import>,
+    pub exports: Option<Vec<String>>,
+    pub functions: Vec<IndexedFunction>,
+    pub constants: Vec<Constant>,
+    pub lines: Vec<String>,
+    pub cells: Vec<Cell>,
+    pub root: usize
+}
+impl Module {
+    /// If the module was read from a Jupyter notebook, which can not be imported
+    pub fn is_notebook(&self) -> bool {
+        !self.cells.is_empty()
+    }
+
+    /// The file and line a line of the module is reported at, the
+    /// notebook cell included
+    pub fn location(&self, line: usize) -> String {
+        let (file_path, line) = locate_line(&self.file_path, &self.cells, line);
+        format!("{}:{}", file_path, line)
+    }
+}
+
+/// All the classes of a project together with the modules defining them,
+/// used for resolving names across files
+#[derive(Debug)]
+pub struct Project {
+    pub classes: Vec<IndexedClass>,
+    pub modules: Vec<Module>,
+    pub roots: Vec<PathBuf>,
+    class_modules: Vec<usize>,
+    modules_by_name: HashMap<String, usize>,
+    definitions: HashMap<(usize, String), Vec<usize>>
+}
+impl Project {
+    /// Indexes all the Python files found in the search roots
+    ///
+    /// # Arguments
+    ///
+    /// * `search_path` - The search directories in priority order, and the revision they are read at
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming each search root which could not be read
+    pub fn load(search_path: &SearchPath) -> Result<Self, String> {
+        Ok(Project::with_roots(search_path.collect()?))
+    }
+
+    /// Indexes the given source files, naming the modules relative to the root
+    pub fn new(root: &Path, sources: Vec<SourceFile>) -> Self {
+        Project::with_roots(vec![(root.to_path_buf(), sources)])
+    }
+
+    /// Indexes the source files of several search roots, naming the modules
+    /// relative to their own root
+    ///
+    /// Like on the Python module search path, a module found in an earlier
+    /// root shadows the module with the same name found in a later one. The
+    /// `.pyi` stubs are indexed after the modules, so the classes defined by
+    /// both a stub and its module are merged into the module class.
+    pub fn with_roots(roots: Vec<(PathBuf, Vec<SourceFile>)>) -> Self {
+        let mut project = Project {
+            classes: Vec::new(),
+            modules: Vec::new(),
+            roots: roots.iter().map(|(root, _)| root.clone()).collect(),
+            class_modules: Vec::new(),
+            modules_by_name: HashMap::new(),
+            definitions: HashMap::new()
+        };
+        let (stubs, modules): (Vec<_>, Vec<_>) = roots.iter()
+            .enumerate()
+            .flat_map(|(root_id, (_, sources))| sources.iter().map(move |source| (root_id, source)))
+            .partition(|(_, source)| is_stub_file(Path::new(&source.path)));
+        for (root_id, source) in modules.into_iter().chain(stubs) {
+            project.add_source(root_id, source);
+        }
+        project
+    }
+
+    fn add_source(&mut self, root_id: usize, source: &SourceFile) {
+        let file_path = Path::new(&source.path);
+        let root = self.roots[root_id].as_path();
+        // The modules packed in an archive are named from the archive root
+        let name = module_name(&module_path(root, &source.path));
+        let is_package = file_path.file_stem()
+            .map(|stem| stem == PACKAGE_FILE)
+            .unwrap_or(false);
+        let lines = source.lines();
+        let implementation = match is_stub_file(file_path) {
+            true => self.modules_by_name.get(&name).copied(),
+            false => None
+        };
+
+        let module_id = self.modules.len();
+        self.modules.push(Module {
+            name: name.clone(),
+            file_path: source.path.clone(),
+            is_package,
+            imports: extract_imports(&lines, &name, is_package),
+            exports: extract_exports(&lines),
+            functions: index_functions(source),
+            constants: extract_constants(source),
+            lines: lines.iter().map(|line| line.trim_end_matches('\r').to_string()).collect(),
+            cells: source.cells.clone(),
+            root: root_id
+        });
+        self.modules_by_name.entry(name).or_insert(module_id);
+
+        for indexed in index_source(source) {
+            let implemented = implementation
+                .and_then(|implementation| self.definitions.get(&(implementation, indexed.class.name.clone())))
+                .and_then(|definitions| definitions.first().copied());
+            if let Some(class_id) = implemented {
+                merge_stub(&mut self.classes[class_id], indexed);
+                continue
+            }
+
+            let class_id = self.classes.len();
+            let definitions = self.definitions
+                .entry((module_id, indexed.class.name.clone()))
+                .or_default();
+            // Module level classes shadow the nested ones with the same name
+            match is_nested(&indexed) {
+                true => definitions.push(class_id),
+                false => definitions.insert(0, class_id)
+            }
+            self.classes.push(indexed);
+            self.class_modules.push(module_id);
+        }
+    }
+
+    /// The module defining a class
+    pub fn module_of(&self, class_id: usize) -> &Module {
+        &self.modules[self.class_modules[class_id]]
+    }
+
+    /// The search root the class was found in
+    pub fn root_of(&self, class_id: usize) -> &Path {
+        &self.roots[self.module_of(class_id).root]
+    }
+
+    /// The position of the module defining a class
+    pub fn module_id_of(&self, class_id: usize) -> usize {
+        self.class_modules[class_id]
+    }
+
+    /// Finds a module by its dotted name or by the path of its file relative
+    /// to the project root, e.g. `billing.invoices` or `billing/invoices.py`
+    pub fn find_module(&self, reference: &str) -> Option<usize> {
+        let is_path = reference.ends_with(PYTHON_FILE_EXTENSION) || reference.contains(['/', '\\']);
+        match is_path {
+            true => self.modules_by_name.get(&module_name(reference)).copied(),
+            false => self.modules_by_name.get(reference).copied()
+        }
+    }
+
+    /// The classes defined in a module, nested ones included, in the order they are written
+    pub fn classes_of(&self, module_id: usize) -> Vec<&IndexedClass> {
+        self.classes.iter()
+            .zip(self.class_modules.iter())
+            .filter(|(_, class_module)| **class_module == module_id)
+            .map(|(indexed, _)| indexed)
+            .collect()
+    }
+
+    /// The modules and packages found directly inside a package
+    pub fn submodules(&self, module_id: usize) -> Vec<&Module> {
+        let package = &self.modules[module_id];
+        if !package.is_package {
+            return Vec::new()
+        }
+        let prefix = format!("{}.", package.name);
+        let mut submodules: Vec<&Module> = self.modules.iter()
+            .filter(|module| module.name.strip_prefix(&prefix)
+                .map(|rest| !rest.contains('.'))
+                .unwrap_or(false))
+            .collect();
+        submodules.sort_by(|first, second| first.name.cmp(&second.name));
+        submodules
+    }
+
+    /// Finds a class by its name or by its dotted path, e.g. `billing.invoices.Invoice`
+    pub fn find_class(&self, name: &str) -> Option<usize> {
+        if let Some((module, class_name)) = name.rsplit_once('.') {
+            if let Some(module_id) = self.modules_by_name.get(module) {
+                return self.resolve_in(*module_id, class_name, 0)
+            }
+        }
+        self.classes.iter().position(|indexed| indexed.class.name == name)
+    }
+
+    /// Finds the module level functions with the name, also given by its
+    /// dotted path, e.g. `billing.invoices.send_invoice`
+    pub fn find_functions(&self, name: &str) -> Vec<&IndexedFunction> {
+        let (modules, name) = self.search_scope(name);
+        modules.iter()
+            .flat_map(|module| module.functions.iter())
+            .filter(|found| found.function.name == name)
+            .collect()
+    }
+
+    /// Finds the module level constants with the name, also given by its dotted path
+    pub fn find_constants(&self, name: &str) -> Vec<&Constant> {
+        let (modules, name) = self.search_scope(name);
+        modules.iter()
+            .flat_map(|module| module.constants.iter())
+            .filter(|found| found.constant.name == name)
+            .collect()
+    }
+
+    /// The modules where a name is searched together with the name without
+    /// its module path, which narrows the search to a single module
+    fn search_scope<'a, 'b>(&'a self, name: &'b str) -> (Vec<&'a Module>, &'b str) {
+        if let Some((module, short_name)) = name.rsplit_once('.') {
+            if let Some(module_id) = self.modules_by_name.get(module) {
+                return (vec![&self.modules[*module_id]], short_name)
+            }
+        }
+        (self.modules.iter().collect(), name)
+    }
+
+    /// Finds the methods of a class, or a single method written as `Class.method`
+    ///
+    /// # Output
+    ///
+    /// * `None` - if neither the class nor the method is found
+    /// * `Some` - the class id and name of each method found
+    pub fn find_methods(&self, name: &str) -> Option<Vec<(usize, String)>> {
+        if let Some(class_id) = self.find_class(name) {
+            let methods: Vec<(usize, String)> = self.classes[class_id].class.methods.iter()
+                .map(|method| (class_id, method.name.clone()))
+                .collect();
+            return Some(methods)
+        }
+        let (class_name, method_name) = name.rsplit_once('.')?;
+        let class_id = self.find_class(class_name)?;
+        self.classes[class_id].class.methods.iter()
+            .find(|method| method.name == method_name)
+            .map(|method| vec![(class_id, method.name.clone())])
+    }
+
+    /// Resolves a name as written in a module to the class it refers to,
+    /// following the imports and their aliases
+    ///
+    /// # Arguments
+    ///
+    /// * `module_id` - The module where the name is written
+    /// * `name` - The written name, e.g. `B`, `base.Base` or `Generic[T]`
+    pub fn resolve(&self, module_id: usize, name: &str) -> Option<usize> {
+        self.resolve_in(module_id, name, 0)
+    }
+
+    /// Resolves a base of a class to the class it refers to
+    ///
+    /// The bases are evaluated before the class name is bound, so a base
+    /// named like the class itself, as in `class Worker(Worker)` extending
+    /// an imported class, refers to the imported class and not to the class.
+    pub fn resolve_base(&self, class_id: usize, base: &str) -> Option<usize> {
+        let module_id = self.module_id_of(class_id);
+        match self.resolve(module_id, base) {
+            Some(base_id) if base_id == class_id => {
+                let name = base.split('[').next().unwrap_or(base).trim();
+                self.resolve_imported(module_id, name, 0)
+                    .filter(|base_id| *base_id != class_id)
+            },
+            resolved => resolved
+        }
+    }
+
+    /// The ready to paste import of a class, e.g. `from billing import InvoiceService`,
+    /// following the shortest public path it can be imported from
+    pub fn import_path(&self, class_id: usize) -> String {
+        let (module, name) = self.public_path(class_id);
+        format!("from {} import {}", module, name)
+    }
+
+    /// Finds the shortest public path a class can be imported from, walking
+    /// the re-exports of the packages defining it, up the package tree
+    ///
+    /// A package re-exports the class if it imports it and, when the package
+    /// defines `__all__`, lists it there. Modules and packages starting with
+    /// an underscore are private and used only if there is no public path.
+    ///
+    /// # Output
+    ///
+    /// The module the class is imported from and the name it is imported by
+    pub fn public_path(&self, class_id: usize) -> (&str, &str) {
+        let class_name = &self.classes[class_id].class.name;
+        let defining = self.module_of(class_id);
+
+        let mut candidates: Vec<(&str, &str)> = vec![(defining.name.as_str(), class_name.as_str())];
+        let mut package = defining.name.as_str();
+        while let Some((parent, _)) = package.rsplit_once('.') {
+            package = parent;
+            let module_id = match self.modules_by_name.get(package) {
+                Some(module_id) => *module_id,
+                None => continue
+            };
+            let module = &self.modules[module_id];
+            let exported = std::iter::once(class_name.as_str())
+                .chain(module.imports.iter().map(|import| import.alias.as_str()))
+                .filter(|name| module.exports.as_ref().map(|exports| exports.iter().any(|export| export == name)).unwrap_or(true))
+                .find(|name| self.resolve(module_id, name) == Some(class_id));
+            if let Some(name) = exported {
+                candidates.push((module.name.as_str(), name));
+            }
+        }
+
+        let is_private = |module: &str| module.split('.').any(|part| part.starts_with('_'));
+        let (module, name) = candidates.iter()
+            .min_by_key(|(module, _)| (is_private(module), module.split('.').count(), module.len()))
+            .copied()
+            .unwrap_or((defining.name.as_str(), class_name.as_str()));
+        (module, name)
+    }
+
+    /// Resolves the bases of a class, each one to its definition in the
+    /// project, up to the root of the hierarchy
+    pub fn ancestors(&self, class_id: usize) -> Vec<Ancestor> {
+        let mut visited: HashSet<usize> = HashSet::new();
+        visited.insert(class_id);
+        self.ancestors_of(class_id, &mut visited)
+    }
+
+    /// Finds the classes inheriting a class, directly or transitively, by
+    /// resolving the bases of every class in the project
+    pub fn subclasses(&self, class_id: usize) -> Vec<Subclass> {
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for child_id in 0..self.classes.len() {
+            for base in self.bases(child_id) {
+                if let Some(base_id) = self.resolve_base(child_id, base) {
+                    children.entry(base_id).or_default().push(child_id);
+                }
+            }
+        }
+
+        let mut visited: HashSet<usize> = HashSet::new();
+        visited.insert(class_id);
+        self.subclasses_of(class_id, &children, &mut visited)
+    }
+
+    /// The bases of a class as written in its header, without keyword arguments
+    pub fn bases(&self, class_id: usize) -> Vec<&String> {
+        self.classes[class_id].class.inheritance.iter()
+            .filter(|base| !base.contains('=') && !base.is_empty())
+            .collect()
+    }
+
+    fn ancestors_of(&self, class_id: usize, visited: &mut HashSet<usize>) -> Vec<Ancestor> {
+        let mut ancestors: Vec<Ancestor> = Vec::new();
+
+        for base in self.bases(class_id) {
+            let ancestor = match self.resolve_base(class_id, base) {
+                Some(base_id) => {
+                    let parents = match visited.insert(base_id) {
+                        true => self.ancestors_of(base_id, visited),
+                        false => Vec::new()
+                    };
+                    visited.remove(&base_id);
+                    let indexed = &self.classes[base_id];
+                    Ancestor::new(base.clone(), indexed.class.name.clone(), Some(indexed.location()), parents)
+                },
+                None => Ancestor::new(base.clone(), base.clone(), None, Vec::new())
+            };
+            ancestors.push(ancestor);
+        }
+        ancestors
+    }
+
+    fn subclasses_of(&self, class_id: usize, children: &HashMap<usize, Vec<usize>>, visited: &mut HashSet<usize>) -> Vec<Subclass> {
+        let mut subclasses: Vec<Subclass> = Vec::new();
+        for child_id in children.get(&class_id).into_iter().flatten() {
+            if !visited.insert(*child_id) {
+                continue
+            }
+            let grand_children = self.subclasses_of(*child_id, children, visited);
+            visited.remove(child_id);
+            let indexed = &self.classes[*child_id];
+            subclasses.push(Subclass::new(indexed.class.name.clone(), indexed.location(), grand_children));
+        }
+        subclasses
+    }
+
+    fn resolve_in(&self, module_id: usize, name: &str, depth: usize) -> Option<usize> {
+        if depth > MAX_RESOLVE_DEPTH {
+            return None
+        }
+        let name = name.split('[').next().unwrap_or(name).trim();
+
+        if let Some((prefix, class_name)) = name.rsplit_once('.') {
+            let target = self.resolve_module(module_id, prefix)?;
+            return self.resolve_in(target, class_name, depth + 1)
+        }
+
+        if let Some(definitions) = self.definitions.get(&(module_id, name.to_string())) {
+            return definitions.first().copied()
+        }
+        self.resolve_imported(module_id, name, depth)
+    }
+
+    /// Resolves a plain name through the imports of a module only, leaving
+    /// out the classes the module defines itself
+    fn resolve_imported(&self, module_id: usize, name: &str, depth: usize) -> Option<usize> {
+        let imports = &self.modules[module_id].imports;
+        for import in imports.iter().filter(|import| import.alias == name) {
+            if let (Some(imported), Some(target)) = (&import.name, self.modules_by_name.get(&import.module)) {
+                if let Some(class_id) = self.resolve_in(*target, imported, depth + 1) {
+                    return Some(class_id)
+                }
+            }
+        }
+        for import in imports.iter().filter(|import| import.name.as_deref() == Some(STAR_IMPORT)) {
+            if let Some(target) = self.modules_by_name.get(&import.module) {
+                if let Some(class_id) = self.resolve_in(*target, name, depth + 1) {
+                    return Some(class_id)
+                }
+            }
+        }
+        None
+    }
+
+    /// Resolves a module reference written in a module, e.g. `models` bound
+    /// by `from app import models` or `import app.models as models`
+    fn resolve_module(&self, module_id: usize, reference: &str) -> Option<usize> {
+        for import in self.modules[module_id].imports.iter() {
+            let module = match &import.name {
+                Some(imported) => format!("{}.{}", import.module, imported),
+                None => import.module.clone()
+            };
+            let absolute = if import.alias == reference {
+                module
+            } else if let Some(rest) = reference.strip_prefix(&format!("{}.", import.alias)) {
+                format!("{}.{}", module, rest)
+            } else {
+                continue
+            };
+            if let Some(target) = self.modules_by_name.get(&absolute) {
+                return Some(*target)
+            }
+        }
+        self.modules_by_name.get(reference).copied()
+    }
+}
+
+/// If the class is defined inside another class or a function
+pub fn is_nested(indexed: &IndexedClass) -> bool {
+    indexed.header.starts_with(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_project() -> Project {
+        let sources = vec![
+            SourceFile::new(String::from("./shop/__init__.py"), String::from(
+                "from .core.base import Base\n"
+            )),
+            SourceFile::new(String::from("./shop/core/base.py"), String::from(
+                "class Base(object):\n    pass\n"
+            )),
+            SourceFile::new(String::from("./shop/models.py"), String::from(
+                "from shop import Base as B\nimport shop.core.base as core\n\nclass Model(B, Generic[T]):\n    pass\n\nclass Other(core.Base, metaclass=Meta):\n    pass\n"
+            )),
+            SourceFile::new(String::from("./shop/orders.py"), String::from(
+                "from . import models\nfrom .models import *\n\nclass Order(models.Model):\n    pass\n\nclass Draft(Order, Other):\n    pass\n"
+            )),
+        ];
+        Project::new(Path::new("."), sources)
+    }
+
+    #[test]
+    fn test_project_modules() {
+        let project = sample_project();
+        let names: Vec<&str> = project.modules.iter().map(|module| module.name.as_str()).collect();
+
+        assert_eq!(names, vec!["shop", "shop.core.base", "shop.models", "shop.orders"]);
+        assert!(project.modules[0].is_package);
+        assert_eq!(project.module_of(0).file_path, "./shop/core/base.py");
+    }
+
+    #[test]
+    fn test_find_module_and_contents() {
+        let project = sample_project();
+        let models = project.find_module("shop/models.py").unwrap();
+
+        assert_eq!(project.find_module("shop.models"), Some(models));
+        assert_eq!(project.find_module("./shop/__init__.py"), Some(0));
+        assert_eq!(project.find_module("shop.payments"), None);
+
+        let names: Vec<&str> = project.classes_of(models).iter().map(|indexed| indexed.class.name.as_str()).collect();
+        assert_eq!(names, vec!["Model", "Other"]);
+
+        let submodules: Vec<&str> = project.submodules(0).iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(submodules, vec!["shop.models", "shop.orders"]);
+        assert!(project.submodules(models).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_aliases_and_reexports() {
+        let project = sample_project();
+        let base = project.find_class("Base").unwrap();
+
+        assert_eq!(project.resolve(2, "B"), Some(base));
+        assert_eq!(project.resolve(2, "core.Base"), Some(base));
+        assert_eq!(project.resolve(3, "Other"), project.find_class("Other"));
+        assert_eq!(project.resolve(2, "Generic"), None);
+        assert_eq!(project.find_class("shop.orders.Order"), project.find_class("Order"));
+    }
+
+    #[test]
+    fn test_import_path() {
+        let project = sample_project();
+        assert_eq!(project.import_path(project.find_class("Base").unwrap()), "from shop import Base");
+        assert_eq!(project.import_path(project.find_class("Order").unwrap()), "from shop.orders import Order");
+
+        let sources = vec![
+            SourceFile::new(String::from("./shop/__init__.py"), String::from(
+                "from ._cart import Cart, Line\n__all__ = ['Cart']\n"
+            )),
+            SourceFile::new(String::from("./shop/_cart.py"), String::from(
+                "class Cart:\n    pass\n\nclass Line:\n    pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        assert_eq!(project.import_path(project.find_class("Cart").unwrap()), "from shop import Cart");
+        assert_eq!(project.import_path(project.find_class("Line").unwrap()), "from shop._cart import Line");
+    }
+
+    #[test]
+    fn test_find_functions_and_constants() {
+        let sources = vec![
+            SourceFile::new(String::from("./shop/cart.py"), String::from(
+                "MAX_ITEMS = 10\n\ndef checkout(cart: Cart) -> Order:\n    pass\n"
+            )),
+            SourceFile::new(String::from("./shop/orders.py"), String::from(
+                "MAX_ITEMS: int = 5\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+
+        assert_eq!(project.find_functions("checkout")[0].location(), "./shop/cart.py:3");
+        assert_eq!(project.find_functions("shop.cart.checkout").len(), 1);
+        assert!(project.find_functions("shop.orders.checkout").is_empty());
+        assert_eq!(project.find_constants("MAX_ITEMS").len(), 2);
+        assert_eq!(project.find_constants("shop.orders.MAX_ITEMS")[0].constant.static_type, "int");
+    }
+
+    #[test]
+    fn test_stub_merged_into_module() {
+        let sources = vec![
+            SourceFile::new(String::from("./shop/_speedups.pyi"), String::from(
+                "class Hasher:\n    def digest(self) -> bytes: ...\n"
+            )),
+            SourceFile::new(String::from("./shop/cart.pyi"), String::from(
+                "class Cart:\n    def total(self) -> int: ...\n"
+            )),
+            SourceFile::new(String::from("./shop/cart.py"), String::from(
+                "class Cart:\n    \"\"\"Items to be ordered.\"\"\"\n    def total(self):\n        pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+
+        assert_eq!(project.classes.len(), 2);
+        let cart = &project.classes[project.find_class("Cart").unwrap()];
+        assert_eq!(cart.location(), "./shop/cart.py:1");
+        assert_eq!(cart.class.methods[0].output, "int");
+        assert_eq!(cart.class.docstring, "Items to be ordered.");
+        assert_eq!(cart.stub.as_ref().unwrap().location, "./shop/cart.pyi:1");
+        assert_eq!(project.classes[project.find_class("Hasher").unwrap()].location(), "./shop/_speedups.pyi:1");
+        assert_eq!(project.find_module("shop.cart"), project.find_module("shop/cart.py"));
+    }
+
+    #[test]
+    fn test_notebook_locations() {
+        let sources = vec![
+            SourceFile::notebook(
+                String::from("./research/churn.ipynb"),
+                String::from("import pandas as pd\n\nclass Model:\n    pass\n"),
+                vec![Cell::new(2, 1), Cell::new(4, 3)]
+            ),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        let model = project.find_class("Model").unwrap();
+
+        assert_eq!(project.classes[model].location(), "./research/churn.ipynb:cell_4:1");
+        assert!(project.module_of(model).is_notebook());
+        assert_eq!(project.module_of(model).location(1), "./research/churn.ipynb:cell_2:1");
+        assert_eq!(project.module_of(model).name, "research.churn");
+    }
+
+    #[test]
+    fn test_archive_module_names() {
+        let sources = vec![
+            SourceFile::new(String::from("./wheelhouse/shop-1.0-py3-none-any.whl!/shop/__init__.py"), String::from(
+                "from .cart import Cart\n"
+            )),
+            SourceFile::new(String::from("./wheelhouse/shop-1.0-py3-none-any.whl!/shop/cart.py"), String::from(
+                "class Cart:\n    pass\n"
+            )),
+        ];
+        let project = Project::new(Path::new("."), sources);
+        let cart = project.find_class("Cart").unwrap();
+
+        assert_eq!(project.module_of(cart).name, "shop.cart");
+        assert_eq!(project.classes[cart].location(), "./wheelhouse/shop-1.0-py3-none-any.whl!/shop/cart.py:1");
+        assert_eq!(project.import_path(cart), "from shop import Cart");
+    }
+
+    #[test]
+    fn test_search_roots_priority() {
+        let roots = vec![
+            (PathBuf::from("./src"), vec![
+                SourceFile::new(String::from("./src/shop/cart.py"), String::from("class Cart:\n    pass\n")),
+            ]),
+            (PathBuf::from("./libs"), vec![
+                SourceFile::new(String::from("./libs/shop/cart.py"), String::from("class Cart(Base):\n    pass\n")),
+                SourceFile::new(String::from("./libs/vendor/client.py"), String::from("class Client:\n    pass\n")),
+            ]),
+        ];
+        let project = Project::with_roots(roots);
+        let cart = project.find_class("shop.cart.Cart").unwrap();
+        let client = project.find_class("vendor.client.Client").unwrap();
+
+        assert_eq!(project.classes[cart].location(), "./src/shop/cart.py:1");
+        assert_eq!(project.root_of(cart), Path::new("./src"));
+        assert_eq!(project.root_of(client), Path::new("./libs"));
+        assert_eq!(project.module_of(client).name, "vendor.client");
+    }
+
+    #[test]
+    fn test_find_methods() {
+        let sources = vec![SourceFile::new(String::from("./shop/cart.py"), String::from(
+            "class Cart:\n    def add(self):\n        pass\n\n    def clear(self):\n        pass\n"
+        ))];
+        let project = Project::new(Path::new("."), sources);
+
+        assert_eq!(project.find_methods("Cart"), Some(vec![(0, String::from("add")), (0, String::from("clear"))]));
+        assert_eq!(project.find_methods("shop.cart.Cart.clear"), Some(vec![(0, String::from("clear"))]));
+        assert_eq!(project.find_methods("Cart.remove"), None);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let project = sample_project();
+        let ancestors = project.ancestors(project.find_class("Order").unwrap());
+
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(ancestors[0].written, "models.Model");
+        assert_eq!(ancestors[0].location, Some(String::from("./shop/models.py:4")));
+
+        let grand_parents = &ancestors[0].parents;
+        assert_eq!(grand_parents[0].written, "B");
+        assert_eq!(grand_parents[0].class_name, "Base");
+        assert!(grand_parents[1].is_external());
+        assert_eq!(grand_parents[0].parents[0].class_name, "object");
+        assert!(grand_parents[0].parents[0].is_external());
+    }
+
+    #[test]
+    fn test_subclasses() {
+        let project = sample_project();
+        let subclasses = project.subclasses(project.find_class("Base").unwrap());
+
+        assert_eq!(subclasses.len(), 2);
+        assert_eq!(subclasses[0].class_name, "Model");
+        assert_eq!(subclasses[0].children[0].class_name, "Order");
+        assert_eq!(subclasses[0].children[0].children[0].class_name, "Draft");
+        assert_eq!(subclasses[0].children[0].children[0].location, "./shop/orders.py:7");
+        assert_eq!(subclasses[1].class_name, "Other");
+        assert_eq!(subclasses[1].children[0].class_name, "Draft");
+        assert!(project.subclasses(project.find_class("Draft").unwrap()).is_empty());
+    }
+}
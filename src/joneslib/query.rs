@@ -0,0 +1,333 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use regex::Regex;
+use super::{
+    docsearch::flatten,
+    filters::{has_base, has_decorator, has_metaclass},
+    objects::IndexedClass,
+    utils::mentions_type
+};
+
+const AND_KEYWORD: &str = "and";
+const OR_KEYWORD: &str = "or";
+const NOT_KEYWORD: &str = "not";
+const OPEN_GROUP: char = '(';
+const CLOSE_GROUP: char = ')';
+const QUOTE: char = '"';
+const KEY_SEPARATOR: char = ':';
+const MISSING_DOCSTRING: &str = "None";
+
+/// Composable filter evaluated over the classes of a project
+///
+/// # Example
+/// ```text
+/// name:*Service and base:BaseService and has:method:close and not decorator:deprecated
+/// ```
+///
+/// Supported predicates:
+///
+/// * `name:<glob>` - the class name matches the glob pattern
+/// * `base:<name>` - the class directly inherits the base
+/// * `decorator:<name>` - the class is decorated with the decorator
+/// * `metaclass:<name>` - the class header sets the metaclass
+/// * `has:method:<glob>` - the class defines a method matching the glob pattern
+/// * `doc:<text>` - the class docstring contains the text, regardless of the case
+/// * `module:<glob>` - the path of the file defining the class, relative to its search root, matches the glob pattern
+/// * `type:<type>` - a method of the class accepts or returns the type
+///
+/// Predicates are combined with `and`, `or`, `not` and parentheses. A word
+/// without a key is the same as `name:<word>`.
+#[derive(Debug)]
+pub enum Query {
+    Name(Regex),
+    Base(String),
+    Decorator(String),
+    Metaclass(String),
+    HasMethod(Regex),
+    Doc(String),
+    Module(Regex),
+    Type(String),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>)
+}
+impl Query {
+    /// Parses a query expression
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing the first syntax error found
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let query = parser.parse_or()?;
+
+        match parser.peek() {
+            None => Ok(query),
+            Some(token) => Err(format!("Unexpected `{}` in query", token))
+        }
+    }
+
+    /// Checks if the class satisfies the query
+    ///
+    /// # Arguments
+    ///
+    /// * `indexed` - The class found in the project
+    /// * `module_path` - The path of the module defining the class, relative to its search root
+    pub fn matches(&self, indexed: &IndexedClass, module_path: &str) -> bool {
+        match self {
+            Query::Name(pattern) => pattern.is_match(&indexed.class.name),
+            Query::Base(base) => has_base(indexed, base),
+            Query::Decorator(decorator) => has_decorator(indexed, decorator),
+            Query::Metaclass(metaclass) => has_metaclass(indexed, metaclass),
+            Query::HasMethod(pattern) => indexed.class.methods.iter()
+                .any(|method| pattern.is_match(&method.name)),
+            Query::Doc(text) => indexed.class.docstring != MISSING_DOCSTRING
+                && flatten(&indexed.class.docstring).to_lowercase().contains(text),
+            Query::Module(pattern) => pattern.is_match(module_path),
+            Query::Type(type_name) => indexed.class.methods.iter()
+                .any(|method| mentions_type(&method.output, type_name, false)
+                    || method.parameters.iter()
+                        .any(|parameter| mentions_type(&parameter.static_type, type_name, false))),
+            Query::Not(query) => !query.matches(indexed, module_path),
+            Query::And(queries) => queries.iter().all(|query| query.matches(indexed, module_path)),
+            Query::Or(queries) => queries.iter().any(|query| query.matches(indexed, module_path))
+        }
+    }
+}
+
+/// Builds an anchored regex out of a glob pattern where `*` matches any
+/// text and `?` matches a single character
+pub fn glob_to_regex(glob: &str) -> Result<Regex, String> {
+    let mut pattern = String::from("^");
+    for chr in glob.chars() {
+        match chr {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(&chr.to_string()))
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).map_err(|err| err.to_string())
+}
+
+/// Splits the expression into words, quoted values and parentheses
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for chr in expression.chars() {
+        match chr {
+            QUOTE => quoted = !quoted,
+            OPEN_GROUP | CLOSE_GROUP if !quoted => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                tokens.push(chr.to_string());
+            },
+            _ if chr.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            },
+            _ => current.push(chr)
+        }
+    }
+    if quoted {
+        return Err(String::from("Unclosed quote in query"))
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser where `not` binds tighter than `and`, which binds tighter than `or`
+struct Parser {
+    tokens: Vec<String>,
+    position: usize
+}
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut queries = vec![self.parse_and()?];
+        while self.peek() == Some(OR_KEYWORD) {
+            self.next();
+            queries.push(self.parse_and()?);
+        }
+        match queries.len() {
+            1 => Ok(queries.remove(0)),
+            _ => Ok(Query::Or(queries))
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut queries = vec![self.parse_not()?];
+        while self.peek() == Some(AND_KEYWORD) {
+            self.next();
+            queries.push(self.parse_not()?);
+        }
+        match queries.len() {
+            1 => Ok(queries.remove(0)),
+            _ => Ok(Query::And(queries))
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Query, String> {
+        if self.peek() == Some(NOT_KEYWORD) {
+            self.next();
+            return Ok(Query::Not(Box::new(self.parse_not()?)))
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, String> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(String::from("Unexpected end of query"))
+        };
+        if token == OPEN_GROUP.to_string() {
+            let query = self.parse_or()?;
+            return match self.next() {
+                Some(token) if token == CLOSE_GROUP.to_string() => Ok(query),
+                _ => Err(String::from("Missing `)` in query"))
+            }
+        }
+        parse_predicate(&token)
+    }
+}
+
+/// Parses a single `key:value` predicate
+fn parse_predicate(token: &str) -> Result<Query, String> {
+    let (key, value) = match token.split_once(KEY_SEPARATOR) {
+        Some((key, value)) => (key, value),
+        None => ("name", token)
+    };
+    if value.is_empty() {
+        return Err(format!("Missing value for `{}` in query", key))
+    }
+
+    match key {
+        "name" => Ok(Query::Name(glob_to_regex(value)?)),
+        "base" => Ok(Query::Base(value.to_string())),
+        "decorator" => Ok(Query::Decorator(value.to_string())),
+        "metaclass" => Ok(Query::Metaclass(value.to_string())),
+        "doc" => Ok(Query::Doc(flatten(value).to_lowercase())),
+        "module" => Ok(Query::Module(glob_to_regex(value)?)),
+        "type" => Ok(Query::Type(value.to_string())),
+        "has" => match value.split_once(KEY_SEPARATOR) {
+            Some(("method", method)) if !method.is_empty() => Ok(Query::HasMethod(glob_to_regex(method)?)),
+            _ => Err(format!("Unknown predicate `has:{}` in query", value))
+        },
+        _ => Err(format!("Unknown predicate `{}` in query", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::{
+        index::{index_source, module_path},
+        sources::SourceFile
+    };
+
+    static PYTHON_CODE: &str = "class OrderService(BaseService):
+    \"\"\"Places customer
+    orders for the shop\"\"\"
+    def close(self, session: Session):
+        pass
+
+@deprecated
+class CartService(BaseService):
+    def close(self):
+        pass
+
+class OrderRepository:
+    def load(self) -> Order:
+        pass
+";
+
+    fn queried(expression: &str) -> Vec<String> {
+        queried_below(expression, ".")
+    }
+
+    fn queried_below(expression: &str, root: &str) -> Vec<String> {
+        let query = Query::parse(expression).unwrap();
+        let source = SourceFile::new(String::from("./shop/services.py"), PYTHON_CODE.to_string());
+        let module_path = module_path(Path::new(root), &source.path);
+        index_source(&source).iter()
+            .filter(|indexed| query.matches(indexed, &module_path))
+            .map(|indexed| indexed.class.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_combined_filters() {
+        let expression = "name:*Service and base:BaseService and has:method:close and not decorator:deprecated";
+        assert_eq!(queried(expression), vec!["OrderService"]);
+    }
+
+    #[test]
+    fn test_query_precedence_and_groups() {
+        assert_eq!(queried("Order* and not base:BaseService or decorator:deprecated"),
+            vec!["CartService", "OrderRepository"]);
+        assert_eq!(queried("Order* and not (base:BaseService or decorator:deprecated)"),
+            vec!["OrderRepository"]);
+    }
+
+    #[test]
+    fn test_query_doc_module_and_type() {
+        assert_eq!(queried("doc:\"customer orders\""), vec!["OrderService"]);
+        assert_eq!(queried("doc:\"customer   orders for\""), vec!["OrderService"]);
+        assert_eq!(queried("module:shop/* and type:Order"), vec!["OrderRepository"]);
+        assert_eq!(queried("type:Session"), vec!["OrderService"]);
+    }
+
+    #[test]
+    fn test_query_module_below_the_root() {
+        assert!(queried_below("module:shop/*", "./shop").is_empty());
+        assert!(queried_below("module:*shop*", "./shop").is_empty());
+        assert_eq!(queried_below("module:services.py", "./shop").len(), 3);
+    }
+
+    #[test]
+    fn test_query_parse_errors() {
+        assert!(Query::parse("name:").is_err());
+        assert!(Query::parse("colour:red").is_err());
+        assert!(Query::parse("(base:Base").is_err());
+        assert!(Query::parse("doc:\"open").is_err());
+        assert!(Query::parse("base:Base base:Other").is_err());
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let pattern = glob_to_regex("*Service?").unwrap();
+
+        assert!(pattern.is_match("OrderServices"));
+        assert!(!pattern.is_match("OrderService"));
+
+        let pattern = glob_to_regex("billing.py").unwrap();
+        assert!(!pattern.is_match("billing_py"));
+    }
+}
@@ -0,0 +1,171 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use super::{
+    imports::module_name,
+    matcher::Matcher,
+    objects::{IndexedClass, RankedClass}
+};
+
+const DOCSTRING_SCORE: u32 = 30;
+const DOCSTRING_REPEAT_SCORE: u32 = 5;
+const DOCSTRING_MAX_SCORE: u32 = 45;
+const METHOD_SCORE: u32 = 20;
+const METHOD_MAX_SCORE: u32 = 40;
+const BASE_SCORE: u32 = 25;
+const MODULE_SCORE: u32 = 15;
+
+/// Scores how relevant a class is for the searched keyword
+///
+/// The class name is matched with the given matcher while the docstring,
+/// method names, base classes and the dotted module name are checked for the
+/// occurrences of the keyword, or of the regular expression in regex mode.
+///
+/// # Arguments
+///
+/// * `indexed` - The class found in the project
+/// * `module_path` - The path of the module defining the class, relative to its search root
+/// * `matcher` - The matcher built from the searched keyword
+///
+/// # Output
+///
+/// * `None` - if no part of the class is related to the keyword
+/// * `Some` - the class with its score and the matched parts
+pub fn rank_class(indexed: IndexedClass, module_path: &str, matcher: &Matcher) -> Option<RankedClass> {
+    if matcher.keyword().is_empty() {
+        return None
+    }
+    let mut score: u32 = 0;
+    let mut hits: Vec<String> = Vec::new();
+
+    if let Some((name_score, _)) = matcher.find(&indexed.class.name) {
+        score += name_score;
+        hits.push(String::from("name"));
+    }
+
+    let docstring_hits = matcher.occurrences(&indexed.class.docstring) as u32;
+    if docstring_hits > 0 {
+        let docstring_score = DOCSTRING_SCORE + (docstring_hits - 1) * DOCSTRING_REPEAT_SCORE;
+        score += docstring_score.min(DOCSTRING_MAX_SCORE);
+        hits.push(String::from("docstring"));
+    }
+
+    let method_hits: Vec<&str> = indexed.class.methods.iter()
+        .filter(|method| matcher.occurrences(&method.name) > 0)
+        .map(|method| method.name.as_str())
+        .collect();
+    if !method_hits.is_empty() {
+        score += (method_hits.len() as u32 * METHOD_SCORE).min(METHOD_MAX_SCORE);
+        hits.push(format!("methods: {}", method_hits.join(", ")));
+    }
+
+    let base_hits: Vec<&str> = indexed.class.inheritance.iter()
+        .filter(|base| matcher.occurrences(base) > 0)
+        .map(|base| base.as_str())
+        .collect();
+    if !base_hits.is_empty() {
+        score += BASE_SCORE;
+        hits.push(format!("bases: {}", base_hits.join(", ")));
+    }
+
+    if matcher.occurrences(&module_name(module_path)) > 0 {
+        score += MODULE_SCORE;
+        hits.push(String::from("module"));
+    }
+
+    match score {
+        0 => None,
+        _ => Some(RankedClass::new(indexed, score, hits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::{
+        matcher::MatchMode,
+        index::{index_source, module_path},
+        sources::SourceFile
+    };
+
+    static PYTHON_CODE: &str = "class StripeGateway(Gateway):
+    \"\"\"Charges payments through Stripe\"\"\"
+    def refund(self, charge_id: str):
+        pass
+
+class PaymentForm:
+    def clean(self):
+        pass
+
+class Logger:
+    def log(self):
+        pass
+";
+
+    fn ranked(keyword: &str, file_path: &str) -> Vec<RankedClass> {
+        ranked_with(keyword, MatchMode::Plain, file_path)
+    }
+
+    fn ranked_with(keyword: &str, mode: MatchMode, file_path: &str) -> Vec<RankedClass> {
+        let matcher = Matcher::new(keyword, mode, true).unwrap();
+        let source = SourceFile::new(file_path.to_string(), PYTHON_CODE.to_string());
+        index_source(&source).into_iter()
+            .filter_map(|indexed| rank_class(indexed, &module_path(Path::new("."), file_path), &matcher))
+            .collect()
+    }
+
+    #[test]
+    fn test_rank_class_by_docstring() {
+        let classes = ranked("payment", "./gateways.py");
+
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].indexed.class.name, "StripeGateway");
+        assert_eq!(classes[0].hits, vec![String::from("docstring")]);
+        assert_eq!(classes[1].indexed.class.name, "PaymentForm");
+        assert_eq!(classes[1].hits, vec![String::from("name")]);
+        assert!(classes[1].score > classes[0].score);
+    }
+
+    #[test]
+    fn test_rank_class_by_methods_bases_and_module() {
+        let classes = ranked("gateway", "./billing/gateway.py");
+
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes[0].hits, vec![
+            String::from("name"),
+            String::from("bases: Gateway"),
+            String::from("module")
+        ]);
+        assert_eq!(classes[2].hits, vec![String::from("module")]);
+    }
+
+    #[test]
+    fn test_rank_class_module_below_the_root() {
+        let matcher = Matcher::new("gateway", MatchMode::Plain, true).unwrap();
+        let source = SourceFile::new(String::from("./gateway/src/billing/forms.py"), PYTHON_CODE.to_string());
+        let classes: Vec<RankedClass> = index_source(&source).into_iter()
+            .filter_map(|indexed| rank_class(indexed, &module_path(Path::new("./gateway/src"), &source.path), &matcher))
+            .collect();
+
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].indexed.class.name, "StripeGateway");
+        assert!(!classes[0].hits.contains(&String::from("module")));
+    }
+
+    #[test]
+    fn test_rank_class_by_regex() {
+        let classes = ranked_with(r"^(refund|clean)$", MatchMode::Regex, "./gateways.py");
+
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].hits, vec![String::from("methods: refund")]);
+        assert_eq!(classes[1].hits, vec![String::from("methods: clean")]);
+    }
+}
@@ -0,0 +1,278 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use super::{
+    is_python_file, is_notebook_file,
+    notebooks::read_notebook,
+    objects::Commit,
+    sources::SourceFile
+};
+
+const GIT: &str = "git";
+const MISSING_OBJECT: &str = "missing";
+const RECORD_SEPARATOR: char = '\x1e';
+const FIELD_SEPARATOR: char = '\x1f';
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%an%x1f%ad%x1f%s";
+
+/// Runs a git command inside a directory, returning its standard output
+fn git(dir_path: &Path, arguments: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new(GIT)
+        .arg("-C")
+        .arg(dir_path)
+        .args(arguments)
+        .output()
+        .map_err(|err| format!("Could not run git: {}", err))?;
+
+    match output.status.success() {
+        true => Ok(output.stdout),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Checks that the revision names a commit of the git repository holding the directory
+///
+/// # Errors
+///
+/// Returns an error if the directory is not inside a git repository or the revision is unknown
+pub fn verify_revision(dir_path: &Path, revision: &str) -> Result<(), String> {
+    let commit = format!("{}^{{commit}}", revision);
+    git(dir_path, &["rev-parse", "--verify", "--quiet", &commit])
+        .map(|_| ())
+        .map_err(|err| match err.is_empty() {
+            true => format!("Unknown revision `{}` in {}", revision, dir_path.display()),
+            false => err
+        })
+}
+
+/// Reads the Python files, stubs and notebooks of a directory as they were at
+/// a revision, straight from the objects of the git repository
+///
+/// The files are named as in the working directory, so the modules and
+/// locations stay the same whatever the revision.
+///
+/// # Arguments
+///
+/// * `dir_path` - The directory inside the git repository
+/// * `revision` - A commit, branch or tag, e.g. `main` or `v1.2.0`
+///
+/// # Output
+///
+/// * `None` - if the revision could not be read
+/// * `Some` - the readable Python files of the directory at that revision
+pub fn collect_revision_sources(dir_path: &Path, revision: &str) -> Option<Vec<SourceFile>> {
+    let listing = git(dir_path, &["ls-tree", "-r", "-z", "--name-only", revision, "--", "."]).ok()?;
+    let files: Vec<String> = String::from_utf8_lossy(&listing)
+        .split('\0')
+        .filter(|file| is_python_file(Path::new(file)) || is_notebook_file(Path::new(file)))
+        .map(String::from)
+        .collect();
+
+    let objects: Vec<String> = files.iter()
+        .map(|file| format!("{}:./{}", revision, file))
+        .collect();
+    let mut sources: Vec<SourceFile> = Vec::new();
+    for (file, content) in files.iter().zip(read_objects(dir_path, &objects)?) {
+        let content = match content {
+            Some(content) => content,
+            None => continue
+        };
+        let path = dir_path.join(file).to_string_lossy().to_string();
+        match is_notebook_file(Path::new(file)) {
+            true => sources.extend(read_notebook(path, &content)),
+            false => sources.push(SourceFile::new(path, content))
+        }
+    }
+    Some(sources)
+}
+
+/// Lists the commits changing a file, newest first, following its renames
+///
+/// # Arguments
+///
+/// * `file_path` - The file in the working directory
+///
+/// # Output
+///
+/// Each commit with the path of the file at that commit, relative to the
+/// root of the repository
+///
+/// # Errors
+///
+/// Returns an error if the file is not inside a git repository or has no commits
+pub fn file_history(file_path: &Path) -> Result<Vec<(Commit, String)>, String> {
+    let dir_path = match file_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new(".")
+    };
+    let file_name = file_path.file_name()
+        .ok_or_else(|| format!("{} is not a file", file_path.display()))?
+        .to_string_lossy();
+    let log = git(dir_path, &["log", "--follow", "--date=short", "--name-only", LOG_FORMAT, "--", &file_name])?;
+
+    let history = parse_log(&String::from_utf8_lossy(&log));
+    match history.is_empty() {
+        true => Err(format!("{} has no commits", file_path.display())),
+        false => Ok(history)
+    }
+}
+
+/// Splits the output of `git log --name-only` written with `LOG_FORMAT` into
+/// the commits and the path of the file at each of them
+fn parse_log(log: &str) -> Vec<(Commit, String)> {
+    log.split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut lines = record.lines().filter(|line| !line.is_empty());
+            let fields: Vec<&str> = lines.next()?.splitn(4, FIELD_SEPARATOR).collect();
+            let path = lines.next()?;
+            match fields.as_slice() {
+                [hash, author, date, summary] => Some((
+                    Commit::new(hash.to_string(), author.to_string(), date.to_string(), summary.to_string()),
+                    path.to_string()
+                )),
+                _ => None
+            }
+        })
+        .collect()
+}
+
+/// Reads the content of git objects with a single `git cat-file --batch`
+///
+/// # Arguments
+///
+/// * `dir_path` - The directory inside the git repository
+/// * `objects` - The objects to read, e.g. `v1.2.0:./shop/cart.py`
+///
+/// # Output
+///
+/// The content of each object in order, `None` for the missing or non UTF-8 ones
+pub fn read_objects(dir_path: &Path, objects: &[String]) -> Option<Vec<Option<String>>> {
+    let mut child = Command::new(GIT)
+        .arg("-C")
+        .arg(dir_path)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // The requests are written from another thread, so git never blocks on
+    // a full output pipe while we are still writing
+    let requests: String = objects.iter()
+        .map(|object| format!("{}\n", object))
+        .collect();
+    let mut stdin = child.stdin.take()?;
+    let writer = thread::spawn(move || stdin.write_all(requests.as_bytes()));
+
+    let output = child.wait_with_output().ok()?;
+    writer.join().ok()?.ok()?;
+    Some(parse_batch(&output.stdout, objects.len()))
+}
+
+/// Splits the output of `git cat-file --batch`, made of a `<object> <type> <size>`
+/// header followed by the content for each requested object
+fn parse_batch(output: &[u8], expected: usize) -> Vec<Option<String>> {
+    let mut blobs: Vec<Option<String>> = Vec::new();
+    let mut position: usize = 0;
+
+    while blobs.len() < expected && position < output.len() {
+        let header_end = match output[position..].iter().position(|byte| *byte == b'\n') {
+            Some(end) => position + end,
+            None => break
+        };
+        let header = String::from_utf8_lossy(&output[position..header_end]).to_string();
+        position = header_end + 1;
+
+        let size = match header.split(' ').collect::<Vec<&str>>().as_slice() {
+            [_, kind, size] if *kind != MISSING_OBJECT => size.parse::<usize>().ok(),
+            _ => None
+        };
+        match size {
+            Some(size) if position + size <= output.len() => {
+                blobs.push(String::from_utf8(output[position..position + size].to_vec()).ok());
+                // The content is followed by a newline
+                position += size + 1;
+            },
+            _ => blobs.push(None)
+        }
+    }
+    blobs
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Runs git in a test repository, as a fixed committer
+    pub(crate) fn run_git(dir_path: &str, arguments: &[&str]) {
+        let status = Command::new(GIT)
+            .args(["-C", dir_path, "-c", "user.name=Jones", "-c", "user.email=jones@example.com"])
+            .args(arguments)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("Could not run git");
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_collect_revision_sources() {
+        let test_dir = "./testing_revisions";
+        fs::create_dir_all(format!("{}/shop", test_dir)).expect("Could not write dir");
+        run_git(test_dir, &["init", "--quiet"]);
+        fs::write(format!("{}/shop/cart.py", test_dir), "class Cart:\n    pass\n").unwrap();
+        fs::write(format!("{}/notes.txt", test_dir), "class Notes:\n").unwrap();
+        run_git(test_dir, &["add", "."]);
+        run_git(test_dir, &["commit", "--quiet", "-m", "Add the cart"]);
+        run_git(test_dir, &["tag", "v1"]);
+        fs::write(format!("{}/shop/cart.py", test_dir), "class Basket:\n    pass\n").unwrap();
+
+        let dir_path = Path::new(test_dir).join("shop");
+        let sources = collect_revision_sources(&dir_path, "v1");
+        let verified = verify_revision(&dir_path, "v1");
+        let unknown = verify_revision(&dir_path, "v2");
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        assert_eq!(sources, Some(vec![SourceFile::new(
+            String::from("./testing_revisions/shop/cart.py"), String::from("class Cart:\n    pass\n")
+        )]));
+        assert_eq!(verified, Ok(()));
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let log = "\x1e5d6e\x1fJane Doe\x1f2021-07-10\x1fRename the cart\n\nshop/cart.py\n\
+                   \x1e1a2b\x1fVlad\x1f2021-07-01\x1fAdd the cart\n\nshop/basket.py\n";
+
+        assert_eq!(parse_log(log), vec![
+            (
+                Commit::new(String::from("5d6e"), String::from("Jane Doe"), String::from("2021-07-10"), String::from("Rename the cart")),
+                String::from("shop/cart.py")
+            ),
+            (
+                Commit::new(String::from("1a2b"), String::from("Vlad"), String::from("2021-07-01"), String::from("Add the cart")),
+                String::from("shop/basket.py")
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let output = b"1a2b blob 5\nhello\nshop/gone.py missing\n3c4d blob 2\nhi\n";
+
+        assert_eq!(parse_batch(output, 3), vec![Some(String::from("hello")), None, Some(String::from("hi"))]);
+    }
+}
@@ -0,0 +1,255 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::{
+    is_python_file, is_notebook_file,
+    archives::{is_archive, read_archive},
+    notebooks::read_notebook,
+    revisions::collect_revision_sources
+};
+
+/// A code cell of a Jupyter notebook, by its position among all the cells
+/// of the notebook and the line where its code starts in the joined source
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq)]
+pub struct Cell {
+    pub index: usize,
+    pub first_line: usize
+}
+impl Cell {
+    pub fn new(index: usize, first_line: usize) -> Self {
+        Cell {
+            index,
+            first_line
+        }
+    }
+}
+
+/// A Python source file read from the project
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+    pub cells: Vec<Cell>
+}
+impl SourceFile {
+    pub fn new(path: String, content: String) -> Self {
+        SourceFile {
+            path,
+            content,
+            cells: Vec::new()
+        }
+    }
+
+    /// A notebook whose code cells were joined into one source
+    pub fn notebook(path: String, content: String, cells: Vec<Cell>) -> Self {
+        SourceFile {
+            path,
+            content,
+            cells
+        }
+    }
+
+    /// The file content split into lines
+    pub fn lines(&self) -> Vec<&str> {
+        self.content.split('\n').collect()
+    }
+
+    /// The file and the line a line of the content is reported at
+    pub fn locate(&self, line: usize) -> (String, usize) {
+        locate_line(&self.path, &self.cells, line)
+    }
+}
+
+/// Translates a line of a joined notebook into its cell, written as
+/// `notebook.ipynb:cell_3` together with the line inside the cell.
+/// The lines of a Python file are kept as they are.
+///
+/// # Arguments
+///
+/// * `path` - The path of the source file
+/// * `cells` - The code cells of the notebook, empty for a Python file
+/// * `line` - The line number in the joined source
+pub fn locate_line(path: &str, cells: &[Cell], line: usize) -> (String, usize) {
+    match cells.iter().rev().find(|cell| cell.first_line <= line) {
+        Some(cell) => (format!("{}:cell_{}", path, cell.index), line - cell.first_line + 1),
+        None => (path.to_string(), line)
+    }
+}
+
+/// The search roots in priority order, read from the working directory or,
+/// when a revision is given, from the git repository at that revision
+///
+/// A root which is itself an archive is always read, while the archives
+/// found inside the root directories are read only when asked for.
+#[derive(Debug, Clone)]
+#[derive(PartialEq)]
+pub struct SearchPath {
+    pub roots: Vec<PathBuf>,
+    pub revision: Option<String>,
+    pub archives: bool
+}
+impl SearchPath {
+    pub fn new(roots: Vec<PathBuf>, revision: Option<String>, archives: bool) -> Self {
+        SearchPath {
+            roots,
+            revision,
+            archives
+        }
+    }
+
+    /// Reads the source files of every root
+    ///
+    /// # Output
+    ///
+    /// Each root with its source files
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming each root which could not be read
+    pub fn collect(&self) -> Result<Vec<(PathBuf, Vec<SourceFile>)>, String> {
+        let mut collected: Vec<(PathBuf, Vec<SourceFile>)> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+
+        for root in self.roots.iter() {
+            let sources = match &self.revision {
+                Some(revision) => collect_revision_sources(root, revision),
+                None if is_archive(root) => read_archive(root),
+                None => collect_sources(root, self.archives)
+            };
+            match (sources, &self.revision) {
+                (Some(sources), _) => collected.push((root.clone(), sources)),
+                (None, Some(revision)) => errors.push(format!("Could not read {} at {}", root.display(), revision)),
+                (None, None) => errors.push(format!("Could not read {}", root.display()))
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(collected),
+            false => Err(errors.join("\n"))
+        }
+    }
+}
+
+/// Recursively reads all the Python files, `.pyi` stubs and Jupyter notebooks
+/// found in a directory, descending into the wheels, zip archives and
+/// source distributions as well when asked to
+///
+/// # Arguments
+///
+/// * `dir_path` - The project directory to be traversed
+/// * `archives` - Whether the archives found in the directory are read
+///
+/// # Output
+///
+/// * `None` - if the directory could not be read
+/// * `Some` - all the readable Python files in traversal order
+pub fn collect_sources(dir_path: &Path, archives: bool) -> Option<Vec<SourceFile>> {
+    let mut sources: Vec<SourceFile> = Vec::new();
+
+    let current_dir = fs::read_dir(dir_path).ok()?;
+
+    for file in current_dir {
+        let file_path = match file {
+            Ok(entry) => entry.path(),
+            Err(_) => continue
+        };
+        if file_path.is_dir() {
+            if let Some(nested) = collect_sources(&file_path, archives) {
+                sources.extend(nested)
+            }
+            continue
+        }
+        if is_archive(&file_path) {
+            if let Some(members) = archives.then(|| read_archive(&file_path)).flatten() {
+                sources.extend(members)
+            }
+            continue
+        }
+        if !is_python_file(&file_path) && !is_notebook_file(&file_path) {
+            continue
+        }
+        let content = match fs::read_to_string(&file_path) {
+            Ok(content) => content,
+            Err(_) => continue
+        };
+        let path = file_path.to_string_lossy().to_string();
+        if is_notebook_file(&file_path) {
+            sources.extend(read_notebook(path, &content));
+            continue
+        }
+        sources.push(SourceFile::new(path, content));
+    }
+
+    Some(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_collect_sources_only_python_files() {
+        let test_dir = "./testing_sources";
+        fs::create_dir_all(format!("{}/nested", test_dir)).expect("Could not write dir");
+        fs::write(format!("{}/nested/module.py", test_dir), "class God:\n    pass\n").unwrap();
+        fs::write(format!("{}/notes.txt", test_dir), "class Notes:\n").unwrap();
+        fs::write(format!("{}/stub.pyi", test_dir), "class Stub: ...\n").unwrap();
+        fs::write(
+            format!("{}/nested/report.ipynb", test_dir),
+            "{\"cells\": [{\"cell_type\": \"code\", \"source\": \"class Report:\\n    pass\"}]}"
+        ).unwrap();
+
+        let mut sources = collect_sources(&PathBuf::from(test_dir), false).unwrap();
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+        sources.sort_by(|first, second| first.path.cmp(&second.path));
+
+        assert_eq!(sources.len(), 3);
+        assert!(sources[0].path.ends_with("module.py"));
+        assert_eq!(sources[0].lines(), vec!["class God:", "    pass", ""]);
+        assert!(sources[1].path.ends_with("report.ipynb"));
+        assert_eq!(sources[1].cells, vec![Cell::new(1, 1)]);
+        assert!(sources[2].path.ends_with("stub.pyi"));
+    }
+
+    #[test]
+    fn test_collect_archives_when_asked() {
+        let test_dir = "./testing_sources_archives";
+        let archive_path = format!("{}/wheelhouse/shop.whl", test_dir);
+        fs::create_dir_all(format!("{}/wheelhouse", test_dir)).expect("Could not write dir");
+        fs::write(format!("{}/module.py", test_dir), "class God:\n    pass\n").unwrap();
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+        writer.start_file("shop/cart.py", zip::write::FileOptions::default()).unwrap();
+        writer.write_all(b"class Cart:\n    pass\n").unwrap();
+        writer.finish().unwrap();
+
+        let skipped = SearchPath::new(vec![PathBuf::from(test_dir)], None, false).collect().unwrap();
+        let missing = SearchPath::new(vec![PathBuf::from(test_dir), PathBuf::from("./testing_missing_root")], None, false).collect();
+        let read = SearchPath::new(vec![PathBuf::from(test_dir)], None, true).collect().unwrap();
+        let explicit = SearchPath::new(vec![PathBuf::from(&archive_path)], None, false).collect().unwrap();
+        fs::remove_dir_all(test_dir).expect("Could not delete dir");
+
+        assert_eq!(skipped[0].1.len(), 1);
+        assert_eq!(read[0].1.len(), 2);
+        assert_eq!(explicit[0].1.len(), 1);
+        assert!(explicit[0].1[0].path.ends_with("shop.whl!/shop/cart.py"));
+        assert_eq!(missing, Err(String::from("Could not read ./testing_missing_root")));
+    }
+
+    #[test]
+    fn test_collect_sources_missing_dir() {
+        assert_eq!(collect_sources(&PathBuf::from("./testing_missing_dir"), false), None);
+    }
+}
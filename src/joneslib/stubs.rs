@@ -0,0 +1,89 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use super::objects::{IndexedClass, StubMerge};
+
+const MISSING_DOCSTRING: &str = "None";
+
+/// Merges the class found in a `.pyi` stub into the class of the implementation
+///
+/// The method signatures come from the stub, including the methods only the
+/// stub declares, while the docstring comes from the implementation unless it
+/// has none. The merged pieces are recorded on the implementation.
+///
+/// # Arguments
+///
+/// * `implementation` - The class defined in the `.py` module
+/// * `stub` - The same class defined in the `.pyi` stub next to the module
+pub fn merge_stub(implementation: &mut IndexedClass, stub: IndexedClass) {
+    let location = stub.location();
+    let mut stub_methods: Vec<String> = Vec::new();
+
+    for method in stub.class.methods {
+        stub_methods.push(method.name.clone());
+        match implementation.class.methods.iter_mut().find(|found| found.name == method.name) {
+            Some(found) => *found = method,
+            None => implementation.class.methods.push(method)
+        }
+    }
+
+    let stub_docstring = implementation.class.docstring == MISSING_DOCSTRING
+        && stub.class.docstring != MISSING_DOCSTRING;
+    if stub_docstring {
+        implementation.class.docstring = stub.class.docstring;
+    }
+    implementation.stub = Some(StubMerge::new(location, stub_methods, stub_docstring));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{
+        index::index_source,
+        sources::SourceFile
+    };
+
+    #[test]
+    fn test_merge_stub() {
+        let implementation = SourceFile::new(String::from("./rates.py"), String::from(
+"class Rates:
+    \"\"\"Currency exchange rates.\"\"\"
+    def convert(self, amount, currency):
+        pass
+
+    def refresh(self):
+        pass
+"
+        ));
+        let stub = SourceFile::new(String::from("./rates.pyi"), String::from(
+"class Rates:
+    def convert(self, amount: int, currency: str) -> float: ...
+    def __len__(self) -> int: ...
+"
+        ));
+        let mut merged = index_source(&implementation).remove(0);
+        merge_stub(&mut merged, index_source(&stub).remove(0));
+
+        let signatures: Vec<String> = merged.class.methods.iter().map(|method| method.signature()).collect();
+        assert_eq!(signatures, vec![
+            "convert(self, amount: int, currency: str) -> float",
+            "refresh(self) -> None",
+            "__len__(self) -> int",
+        ]);
+        assert_eq!(merged.class.docstring, "Currency exchange rates.");
+        assert!(merged.is_stub_method("convert"));
+        assert!(!merged.is_stub_method("refresh"));
+        assert_eq!(merged.stub, Some(StubMerge::new(
+            String::from("./rates.pyi:1"),
+            vec![String::from("convert"), String::from("__len__")],
+            false
+        )));
+    }
+}
@@ -0,0 +1,233 @@
+/*
+JonesCLI
+
+Author: Vlad Nedelcu
+Date: Jul 2021
+License: MIT
+
+Copyright 2021 Vlad Nedelcu
+*/
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use regex::Regex;
+use super::{
+    objects::{Usage, UsageKind},
+    project::Project,
+    utils::{extract_class_name, strip_comment}
+};
+
+const TRIPLE_QUOTE: &str = "\"\"\"";
+const INSTANCE_CHECKS: [&str; 2] = ["isinstance", "issubclass"];
+const IMPORT_KEYWORDS: [&str; 2] = ["from ", "import "];
+
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z_]\w*(?:\.[A-Za-z_]\w*)*").unwrap()
+});
+
+/// Finds every line of the project using a class: imports, constructor
+/// calls, `isinstance`/`issubclass` checks, type annotations and subclassing
+///
+/// The class is followed through aliased imports (`from .base import Base as B`)
+/// and module paths (`models.Base`), by resolving each written name back to
+/// its definition.
+///
+/// # Arguments
+///
+/// * `project` - The loaded project
+/// * `class_id` - The class whose usages are searched
+///
+/// # Output
+///
+/// The usages ordered by kind and then by location, at most one per kind and line
+pub fn find_usages(project: &Project, class_id: usize) -> Vec<Usage> {
+    let indexed = &project.classes[class_id];
+    let class_name = indexed.class.name.as_str();
+    let mut usages: Vec<Usage> = Vec::new();
+
+    for (module_id, module) in project.modules.iter().enumerate() {
+        let bound_names: HashSet<&str> = module.imports.iter()
+            .map(|import| import.alias.as_str())
+            .chain(std::iter::once(class_name))
+            .filter(|name| project.resolve(module_id, name) == Some(class_id))
+            .collect();
+        let defines_class = project.module_id_of(class_id) == module_id;
+
+        let mut in_docstring: bool = false;
+        for (counter, line) in module.lines.iter().enumerate() {
+            if line.matches(TRIPLE_QUOTE).count() % 2 == 1 {
+                in_docstring = !in_docstring;
+                continue
+            }
+            let location = module.location(counter + 1);
+            if in_docstring || (defines_class && location == indexed.location()) {
+                continue
+            }
+            let code = strip_comment(line);
+
+            let mut kinds: Vec<UsageKind> = Vec::new();
+            for found in REFERENCE.find_iter(code) {
+                if code[..found.start()].ends_with('.') {
+                    continue
+                }
+                let length = match referenced_length(project, module_id, class_id, &bound_names, found.as_str()) {
+                    Some(length) => length,
+                    None => continue
+                };
+                let kind = usage_kind(code, found.start(), found.start() + length);
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            }
+            for kind in kinds {
+                usages.push(Usage::new(kind, line.trim().to_string(), location.clone()));
+            }
+        }
+    }
+    usages.sort_by_key(|usage| usage.kind);
+    usages
+}
+
+/// The length of the part of a dotted name referring to the class, if any
+///
+/// # Example
+/// `models.Base.create` refers to `Base` through its first 11 characters
+fn referenced_length(
+    project: &Project, module_id: usize, class_id: usize, bound_names: &HashSet<&str>, written: &str
+) -> Option<usize> {
+    let class_name = project.classes[class_id].class.name.as_str();
+    let mut length: usize = 0;
+
+    for (position, segment) in written.split('.').enumerate() {
+        length += segment.len() + if position > 0 { 1 } else { 0 };
+        let referenced = match position {
+            0 => bound_names.contains(segment),
+            _ => segment == class_name && project.resolve(module_id, &written[..length]) == Some(class_id)
+        };
+        if referenced {
+            return Some(length)
+        }
+    }
+    None
+}
+
+/// Classifies a reference to the class by the code written around it
+fn usage_kind(code: &str, start: usize, end: usize) -> UsageKind {
+    let statement = code.trim_start();
+    if IMPORT_KEYWORDS.iter().any(|keyword| statement.starts_with(keyword)) {
+        return UsageKind::Import
+    }
+    let before = &code[..start];
+    if extract_class_name(code).is_some() && before.contains('(') {
+        return UsageKind::Subclass
+    }
+
+    let open_brackets = open_brackets(before);
+    if open_brackets.iter().any(|(bracket, called)| *bracket == '(' && INSTANCE_CHECKS.contains(called)) {
+        return UsageKind::InstanceCheck
+    }
+    if code[end..].trim_start().starts_with('(') {
+        return UsageKind::Instantiation
+    }
+
+    let written_before = before.trim_end().trim_end_matches(['"', '\'']).trim_end();
+    let in_subscript = matches!(open_brackets.last(), Some(('[', called)) if !called.is_empty());
+    if in_subscript || written_before.ends_with(':') || written_before.ends_with("->") {
+        return UsageKind::Annotation
+    }
+    UsageKind::Reference
+}
+
+/// The brackets left open before a position, each one with the name written
+/// right before it, e.g. `isinstance` for `isinstance(`
+fn open_brackets(code: &str) -> Vec<(char, &str)> {
+    let mut open: Vec<(char, &str)> = Vec::new();
+    for (position, chr) in code.char_indices() {
+        match chr {
+            '(' | '[' => {
+                let before = &code[..position];
+                let called_start = before
+                    .rfind(|chr: char| !(chr.is_alphanumeric() || chr == '_' || chr == '.'))
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+                open.push((chr, &before[called_start..]));
+            },
+            ')' | ']' => {
+                open.pop();
+            },
+            _ => continue
+        }
+    }
+    open
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use super::super::sources::SourceFile;
+
+    fn billing_project() -> Project {
+        let sources = vec![
+            SourceFile::new(String::from("./billing/__init__.py"), String::from(
+                "from .invoices import Invoice\n"
+            )),
+            SourceFile::new(String::from("./billing/invoices.py"), String::from(
+"class Invoice:
+    \"\"\"
+    Invoice(number) is a bill
+    \"\"\"
+    def copy(self) -> \"Invoice\":
+        return Invoice(self.number)
+"
+            )),
+            SourceFile::new(String::from("./billing/reports.py"), String::from(
+"from billing import Invoice as Bill
+import billing.invoices as inv
+
+class Report(Bill):
+    invoices: List[Bill] = []
+
+    def add(self, invoice: Optional[inv.Invoice]) -> None:
+        if isinstance(invoice, (Bill, Draft)):
+            self.invoices.append(inv.Invoice.copy(invoice))
+        registry.register(Bill)  # Bill() in a comment
+        label = \"#\"; Bill()
+"
+            )),
+        ];
+        Project::new(Path::new("."), sources)
+    }
+
+    #[test]
+    fn test_find_usages() {
+        let project = billing_project();
+        let usages = find_usages(&project, project.find_class("Invoice").unwrap());
+        let found: Vec<(UsageKind, &str)> = usages.iter()
+            .map(|usage| (usage.kind, usage.location.as_str()))
+            .collect();
+
+        assert_eq!(found, vec![
+            (UsageKind::Import, "./billing/__init__.py:1"),
+            (UsageKind::Import, "./billing/reports.py:1"),
+            (UsageKind::Instantiation, "./billing/invoices.py:6"),
+            (UsageKind::Instantiation, "./billing/reports.py:11"),
+            (UsageKind::InstanceCheck, "./billing/reports.py:8"),
+            (UsageKind::Annotation, "./billing/invoices.py:5"),
+            (UsageKind::Annotation, "./billing/reports.py:5"),
+            (UsageKind::Annotation, "./billing/reports.py:7"),
+            (UsageKind::Subclass, "./billing/reports.py:4"),
+            (UsageKind::Reference, "./billing/reports.py:9"),
+            (UsageKind::Reference, "./billing/reports.py:10"),
+        ]);
+        assert_eq!(usages[1].code, "from billing import Invoice as Bill");
+    }
+
+    #[test]
+    fn test_usage_kind() {
+        assert_eq!(usage_kind("x = Invoice()", 4, 11), UsageKind::Instantiation);
+        assert_eq!(usage_kind("def load() -> Invoice:", 14, 21), UsageKind::Annotation);
+        assert_eq!(usage_kind("issubclass(kind, Invoice)", 17, 24), UsageKind::InstanceCheck);
+        assert_eq!(usage_kind("handlers = [Invoice]", 12, 19), UsageKind::Reference);
+    }
+}
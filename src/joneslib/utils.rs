@@ -8,19 +8,36 @@ License: MIT
 Copyright 2021 Vlad Nedelcu
 */
 
+use std::sync::LazyLock;
 use regex::Regex;
 use super::{
     objects::{
         Parameter,
-        Method
+        Method,
+        ClassMatch,
+        IndexedClass,
+        MethodMatch,
+        TypeMatch
     },
-    markers::get_header_arguments
+    matcher::Matcher,
+    markers::get_header_arguments,
+    ASYNC_KEYWORD
 };
 
 static FUNCTION_KEYWORD: &str = " def ";
 static DEFAULT_TYPE: &str = "None";
 static ENDEF_KEYWORD: char = ':';
-static CLASS_KEYWORD: &str = "class ";
+static DECORATOR_KEYWORD: char = '@';
+const COMMENT: char = '#';
+static CLASS_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*class\s+(\w+)").unwrap()
+});
+static CLASS_ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z_]\w*)\s*(?::\s*([^=]+?)\s*(?:=.*)?|=(?:[^=].*)?)$").unwrap()
+});
+static SELF_ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bself\.([A-Za-z_]\w*)\s*(?::\s*([^=]+?))?\s*=[^=]").unwrap()
+});
 
 /// Simple regex split on a given code line
 /// # Arguments
@@ -28,7 +45,7 @@ static CLASS_KEYWORD: &str = "class ";
 /// * `r`: Regex string line
 /// * `trim`: boolean if you want to trim the values
 /// * `value`: value to split
-fn regex_split<'a>(r: &'a str, trim: bool, value: &'a String) -> Vec<&'a str> {
+fn regex_split<'a>(r: &str, trim: bool, value: &'a str) -> Vec<&'a str> {
     let regex_separator = Regex::new(r).expect("Invalid regex function given");
     match trim {
         true => regex_separator.split(value.trim()).collect(),
@@ -46,9 +63,11 @@ fn regex_split<'a>(r: &'a str, trim: bool, value: &'a String) -> Vec<&'a str> {
 /// ```python
 /// def method_name(self, arg1: int, arg2: str) -> None:
 /// ```
-/// Extracted name here is `method_name`
-pub fn extract_method_name(method_header: &String) -> Result<String, &str> {
-    let split_header = regex_split(r"\W", true, method_header);
+/// Extracted name here is `method_name`, the same as for an `async def`
+pub fn extract_method_name(method_header: &str) -> Result<String, &str> {
+    let definition = method_header.trim_start();
+    let definition = definition.strip_prefix(ASYNC_KEYWORD).unwrap_or(definition);
+    let split_header = regex_split(r"\W", true, definition);
     if split_header[0].trim() != FUNCTION_KEYWORD.trim() {
         return Err("This is not a method header")
     }
@@ -60,7 +79,7 @@ pub fn extract_method_name(method_header: &String) -> Result<String, &str> {
 /// # Arguments
 ///
 /// * `header`: - The header line from a Python method
-pub fn extract_parameters(header: &String) -> Vec<Parameter> {
+pub fn extract_parameters(header: &str) -> Vec<Parameter> {
 
     // Split to get all the parameter
     let parameter_segment: String = match get_header_arguments(header) {
@@ -95,19 +114,42 @@ pub fn extract_parameters(header: &String) -> Vec<Parameter> {
                     )
                 )
             },
-            _ => println!("Found method in code with no parameters. {:?}", param_values)
+            // A parameter with several annotation separators is not supported
+            _ => continue
         }
 
     }
     parameters
 }
 
+/// Finds the colon ending a `def` or `class` header, skipping the colons
+/// of the annotations and default values written between brackets, and
+/// the brackets and colons written inside strings and comments
+///
+/// # Example
+/// ```python
+/// def total(self, rates: Dict[str, int]) -> int: ...
+/// ```
+/// The header ends at the colon before `...`
+pub fn header_end(header: &str) -> Option<usize> {
+    let mut depth: i32 = 0;
+    for (position, chr) in mask_strings(header).char_indices() {
+        match chr {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ if chr == ENDEF_KEYWORD && depth == 0 => return Some(position),
+            _ => continue
+        }
+    }
+    None
+}
+
 /// Extract methods found in a Python class
 ///
 /// # Arguments
 ///
 /// * `class_code` - The code for the Python class extracted from the
-/// .py file
+///   .py file
 pub fn extract_methods(class_code: Vec<String>) -> Vec<Method> {
 
     // Initialize temp method and start for retrieving method headers
@@ -121,15 +163,11 @@ pub fn extract_methods(class_code: Vec<String>) -> Vec<Method> {
             start = true;
         }
         if start {
-            temp_method.push_str(format!(" {}", line.trim()).as_str());
-            let last_char = match temp_method.chars()
-                .nth(temp_method.len() - 1) {
-                    Some(chr) => chr,
-                    None => continue
-                };
-
-            if last_char == ENDEF_KEYWORD {
-                methods.push(Method::new(&temp_method));
+            temp_method.push_str(format!(" {}", strip_comment(line).trim()).as_str());
+
+            // The body of stubs and one line methods follows the header
+            if let Some(end) = header_end(&temp_method) {
+                methods.push(Method::new(&temp_method[..=end]));
                 temp_method = String::new();
                 start = false;
             }
@@ -148,7 +186,7 @@ pub fn extract_methods(class_code: Vec<String>) -> Vec<Method> {
 ///
 /// * `Err` - if the header had no type and at split nothing happened
 /// * `Ok` - returns header type
-pub fn extract_method_output(header: &String) -> Result<String, &str> {
+pub fn extract_method_output(header: &str) -> Result<String, &str> {
     let header_split = regex_split(r"( -> )", true, header);
     match header_split.len() {
         1 => Err("Output type not found"),
@@ -156,31 +194,331 @@ pub fn extract_method_output(header: &String) -> Result<String, &str> {
     }
 }
 
-/// Search & find all Python classes that contain the keyword or are relevant to the context
+/// Extract the class name from a class header line
+///
+/// # Arguments
+///
+/// * `line` - The class header code line
+///
+/// # Example
+/// ```python
+/// class ClassName(Base):
+/// ```
+/// Extracted name here is `ClassName` together with its byte offset in the line
+pub fn extract_class_name(line: &str) -> Option<(usize, &str)> {
+    let name = CLASS_HEADER.captures(line)?.get(1)?;
+    Some((name.start(), name.as_str()))
+}
+
+/// Match the name of an indexed class against the keyword
+///
+/// # Arguments
+///
+/// * `indexed` - The class found in the project
+/// * `matcher` - The matcher built from the keyword given for the search
+///
+/// # Output
+///
+/// * `Option<ClassMatch>` - the class header with its score if the name matched
+///
+/// An empty keyword keeps every class, so the grep filters alone can select them.
+pub fn grep_class(indexed: &IndexedClass, matcher: &Matcher) -> Option<ClassMatch> {
+    let (offset, name) = extract_class_name(&indexed.header)?;
+    let (score, spans) = match matcher.keyword().is_empty() {
+        true => (0, Vec::new()),
+        false => matcher.find(name)?
+    };
+    let line_spans = spans.iter()
+        .map(|(start, end)| (start + offset, end + offset))
+        .collect();
+
+    Some(ClassMatch::new(indexed.header.clone(), indexed.file_path.clone(), score, line_spans))
+}
+
+/// Find all the methods of an indexed class whose name is matched by the keyword
+///
+/// # Arguments
+///
+/// * `indexed` - The class found in the project
+/// * `matcher` - The matcher built from the method name or pattern
+pub fn grep_methods(indexed: IndexedClass, matcher: &Matcher) -> Vec<MethodMatch> {
+    let locations: Vec<String> = indexed.class.methods.iter()
+        .map(|method| indexed.method_location(&method.name))
+        .collect();
+    let class_name = indexed.class.name;
+
+    indexed.class.methods.into_iter()
+        .zip(locations)
+        .filter_map(|(method, location)| {
+            let (score, spans) = matcher.find(&method.name)?;
+            Some(MethodMatch::new(class_name.clone(), method, location, score, spans))
+        })
+        .collect()
+}
+
+/// Check if a type annotation mentions the searched type
+///
+/// A single name is looked up among the names used by the annotation, so
+/// `Session` is found in `Optional[Session]`. A full type expression such as
+/// `Optional[User]` is looked up in the annotation ignoring the whitespace.
+///
+/// # Arguments
+///
+/// * `static_type` - The type annotation as written in code
+/// * `type_name` - The searched type
+/// * `ignore_case` - Compare without taking the letter case into account
+pub fn mentions_type(static_type: &str, type_name: &str, ignore_case: bool) -> bool {
+    let normalize = |value: &str| -> String {
+        let compact: String = value.chars()
+            .filter(|chr| !chr.is_whitespace() && *chr != '\'' && *chr != '"')
+            .collect();
+        match ignore_case {
+            true => compact.to_lowercase(),
+            false => compact
+        }
+    };
+    let annotation = normalize(static_type);
+    let searched = normalize(type_name);
+    if searched.is_empty() {
+        return false
+    }
+
+    if searched.chars().all(|chr| chr.is_alphanumeric() || chr == '_' || chr == '.') {
+        regex_split(r"[^\w.]+", false, &annotation).iter()
+            .any(|name| *name == searched || name.rsplit('.').next() == Some(searched.as_str()))
+    } else {
+        annotation.contains(&searched)
+    }
+}
+
+/// Find all the parameters and return values of an indexed class annotated with a type
+///
+/// # Arguments
+///
+/// * `indexed` - The class found in the project
+/// * `type_name` - The searched type
+/// * `ignore_case` - Compare without taking the letter case into account
+pub fn grep_types(indexed: IndexedClass, type_name: &str, ignore_case: bool) -> Vec<TypeMatch> {
+    let mut found_types: Vec<TypeMatch> = Vec::new();
+    let locations: Vec<String> = indexed.class.methods.iter()
+        .map(|method| indexed.method_location(&method.name))
+        .collect();
+
+    for (method, location) in indexed.class.methods.into_iter().zip(locations) {
+        let output_matches = mentions_type(&method.output, type_name, ignore_case);
+        for parameter in method.parameters.into_iter() {
+            if mentions_type(&parameter.static_type, type_name, ignore_case) {
+                found_types.push(TypeMatch::new(
+                    indexed.class.name.clone(),
+                    method.name.clone(),
+                    Some(parameter),
+                    method.output.clone(),
+                    location.clone()
+                ));
+            }
+        }
+        if output_matches {
+            found_types.push(TypeMatch::new(
+                indexed.class.name.clone(),
+                method.name,
+                None,
+                method.output,
+                location
+            ));
+        }
+    }
+    found_types
+}
+
+/// Extract the decorators written right above a class or function header
 ///
 /// # Arguments
 ///
 /// * `lines` - The python file code lines previously read
-/// * `keyword` - The class name given for the search
+/// * `header_index` - The index of the class or function header line
 ///
 /// # Output
 ///
-/// * `Option<Vec<String, String>>` - containing all the found relevant classes
-pub fn grep_class<'a>(lines: Vec<&str>, keyword: &String, file_name: &str) -> Option<Vec<(String, String)>> {
-    let mut found_match_classes: Vec<(String, String)> = Vec::new();
-    for line in lines.iter() {
-        if line.trim().starts_with(CLASS_KEYWORD) && line.contains(keyword) {
-            found_match_classes.push(
-                (line.to_string(), file_name.to_string())
-            );
+/// The decorators without the `@` sign, in the order they are written. The
+/// arguments of a decorator spanning several lines are joined on one line.
+pub fn extract_decorators(lines: &[&str], header_index: usize) -> Vec<String> {
+    let mut decorators: Vec<String> = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+
+    // Walk up from the header, collecting the lines until they form a whole
+    // decorator; a line outside of any open decorator ends the search
+    for line in lines[..header_index].iter().rev() {
+        pending.insert(0, line.trim());
+        let depth = bracket_depth(&pending.join(" "));
+        if pending[0].starts_with(DECORATOR_KEYWORD) && depth == 0 {
+            let decorator = pending.join(" ")
+                .replace("( ", "(")
+                .replace(" )", ")");
+            decorators.push(decorator.trim_start_matches(DECORATOR_KEYWORD).to_string());
+            pending.clear();
+        } else if depth >= 0 {
+            break
+        }
+    }
+    decorators.reverse();
+    decorators
+}
+
+/// The opened minus the closed brackets of a code text, leaving out the
+/// ones written inside string literals and comments
+pub fn bracket_depth(text: &str) -> i32 {
+    mask_strings(text).chars()
+        .map(|chr| match chr {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0
+        })
+        .sum()
+}
+
+/// The code text with the content of its string literals and its comment
+/// blanked out, keeping every code character at the same position
+///
+/// # Example
+/// ```python
+/// def split(self, sep="#"):  # Splits the text
+/// ```
+/// Masked text here is `def split(self, sep=" "):` followed by spaces
+pub fn mask_strings(text: &str) -> String {
+    let code = strip_comment(text);
+    let mut masked = String::with_capacity(text.len());
+    let mut quote: Option<char> = None;
+    let mut escaped: bool = false;
+
+    for chr in code.chars() {
+        let is_code = match quote {
+            Some(opened) => {
+                let closes = chr == opened && !escaped;
+                escaped = chr == '\\' && !escaped;
+                if closes {
+                    quote = None;
+                }
+                closes
+            },
+            None => {
+                if chr == '"' || chr == '\'' {
+                    quote = Some(chr);
+                }
+                true
+            }
+        };
+        match is_code {
+            true => masked.push(chr),
+            false => masked.extend(std::iter::repeat_n(' ', chr.len_utf8()))
         }
     }
-    match found_match_classes.len() {
-        0 => None,
-        _ => Some(found_match_classes)
+    masked.extend(std::iter::repeat_n(' ', text.len() - code.len()));
+    masked
+}
+
+/// The code text without its comment, which starts at the first `#`
+/// written outside of a string literal
+pub fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped: bool = false;
+
+    for (position, chr) in text.char_indices() {
+        match quote {
+            Some(opened) => {
+                if chr == opened && !escaped {
+                    quote = None;
+                }
+                escaped = chr == '\\' && !escaped;
+            },
+            None if chr == '"' || chr == '\'' => quote = Some(chr),
+            None if chr == COMMENT => return &text[..position],
+            None => continue
+        }
+    }
+    text
+}
+
+/// Extract the name of a decorator, ignoring its module path and call arguments
+///
+/// # Example
+/// ```python
+/// @registry.register(name='board')
+/// ```
+/// Extracted name here is `register`
+pub fn decorator_name(decorator: &str) -> &str {
+    let called = decorator.split('(').next().unwrap_or(decorator);
+    called.rsplit('.').next().unwrap_or(called).trim()
+}
+
+/// Extract the attributes of a class: the ones assigned or annotated in the
+/// class body and the ones assigned on `self` inside its methods
+///
+/// # Arguments
+///
+/// * `class_code` - The class code block, header included
+///
+/// # Output
+///
+/// The attributes in the order they are first written, typed with their
+/// annotation or `None` when they have none
+pub fn extract_attributes(class_code: &[String]) -> Vec<Parameter> {
+    let body_indent = class_code.iter()
+        .skip(1)
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len());
+
+    let mut attributes: Vec<Parameter> = Vec::new();
+    let mut in_docstring: bool = false;
+    for line in class_code.iter().skip(1) {
+        let code = line.trim();
+        if code.matches("\"\"\"").count() % 2 == 1 {
+            in_docstring = !in_docstring;
+            continue
+        }
+        if in_docstring || code.starts_with(FUNCTION_KEYWORD.trim_start()) {
+            continue
+        }
+
+        let at_class_level = Some(line.len() - line.trim_start().len()) == body_indent;
+        let captures = match at_class_level {
+            true => CLASS_ATTRIBUTE.captures(code),
+            false => SELF_ATTRIBUTE.captures(code)
+        };
+        let captures = match captures {
+            Some(captures) => captures,
+            None => continue
+        };
+        let name = captures[1].to_string();
+        if attributes.iter().any(|attribute| attribute.name == name) {
+            continue
+        }
+        let static_type = captures.get(2)
+            .map(|annotation| annotation.as_str().trim().to_string())
+            .unwrap_or_else(|| DEFAULT_TYPE.to_string());
+        attributes.push(Parameter::new(name, static_type));
     }
+    attributes
 }
 
+/// Extract the keyword arguments written in a class header, e.g. the metaclass
+///
+/// # Arguments
+///
+/// * `line` - Is the class header previously extracted
+///
+/// # Output
+///
+/// A vector with the keyword names and their values
+pub fn extract_class_keywords(line: &str) -> Vec<(String, String)> {
+    extract_class_inheritance(line)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            let (keyword, value) = entry.split_once('=')?;
+            Some((keyword.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
 
 /// Extract class inheritance objects
 ///
@@ -193,7 +531,7 @@ pub fn grep_class<'a>(lines: Vec<&str>, keyword: &String, file_name: &str) -> Op
 /// # Output
 ///
 /// A vector containing all the objects the class inherits
-pub fn extract_class_inheritance(line: &String) -> Option<Vec<String>> {
+pub fn extract_class_inheritance(line: &str) -> Option<Vec<String>> {
 
     // Split the header for now to get objects that are inherited by the class
     let class_header_split: Vec<&str> = regex_split(r"(\(|\):|,)", true, line);
@@ -214,6 +552,16 @@ pub fn extract_class_inheritance(line: &String) -> Option<Vec<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::matcher::MatchMode;
+    use super::super::index::index_source;
+    use super::super::sources::SourceFile;
+
+    fn grep_lines(lines: Vec<&str>, matcher: &Matcher, file_name: &str) -> Vec<ClassMatch> {
+        let source = SourceFile::new(file_name.to_string(), lines.join("\n"));
+        index_source(&source).iter()
+            .filter_map(|indexed| grep_class(indexed, matcher))
+            .collect()
+    }
 
     #[test]
     fn test_regex_split_positive(){
@@ -243,6 +591,19 @@ mod tests {
         assert_eq!(extract_method_name(&test_string).unwrap(), expected);
     }
 
+    #[test]
+    fn test_extract_async_method_name(){
+        assert_eq!(extract_method_name("    async def run(self) -> None:"), Ok(String::from("run")));
+        assert_eq!(Method::new("async def run(self):").name, "run");
+    }
+
+    #[test]
+    fn test_mask_strings(){
+        assert_eq!(mask_strings(r##"f(sep="#", end='(') # call"##), r#"f(sep=" ", end=' ')       "#);
+        assert_eq!(mask_strings(r#"s = "a\"(""#), r#"s = "    ""#);
+        assert_eq!(bracket_depth(r#"CHOICES = [("(", "open"),"#), 1);
+    }
+
     #[test]
     fn test_extract_method_name_negative(){
         let test_string = String::from("import definition as positive");
@@ -292,8 +653,8 @@ mod tests {
         ];
 
         let expected_methods = vec![
-            Method::new(&"    def __init__(self, name):".to_string()),
-            Method::new(&"    def say_hi(self):".to_string()),
+            Method::new("    def __init__(self, name):"),
+            Method::new("    def say_hi(self):"),
         ];
 
         assert_eq!(extract_methods(test_codebase), expected_methods);
@@ -315,13 +676,53 @@ mod tests {
         ];
 
         let expected_methods = vec![
-            Method::new(&"    def __init__(self, name: int, param1: str, param2: int) -> str:".to_string()),
-            Method::new(&"    def say_hi(self):".to_string()),
+            Method::new("    def __init__(self, name: int, param1: str, param2: int) -> str:"),
+            Method::new("    def say_hi(self):"),
         ];
 
         assert_eq!(extract_methods(test_codebase), expected_methods);
     }
 
+    #[test]
+    fn test_extract_methods_stub_bodies(){
+        let test_codebase: Vec<String> = [
+            "class Rates:",
+            "    def total(self, rates: Dict[str, int]) -> int: ...",
+            "    def clear(self): pass  # one line",
+        ].iter().map(|line| line.to_string()).collect();
+        let methods = extract_methods(test_codebase);
+
+        assert_eq!(methods.len(), 2);
+        assert_eq!(methods[0].output, "int");
+        assert_eq!(methods[0].parameters[1].static_type, "Dict[str,int]");
+        assert_eq!(methods[1].name, "clear");
+        assert_eq!(header_end("def load(self,"), None);
+    }
+
+    #[test]
+    fn test_header_end_skips_strings_and_comments(){
+        assert_eq!(header_end(r##"def split(self, sep="#"):"##), Some(24));
+        assert_eq!(header_end(r#"def f(self, s="("):"#), Some(18));
+        assert_eq!(header_end("def f(self, s=':'):  # returns: None"), Some(18));
+        assert_eq!(header_end("def f(self,  # the receiver"), None);
+        assert_eq!(strip_comment(r##"sep = "#"  # separator"##), r##"sep = "#"  "##);
+
+        let test_codebase = vec![
+            "class Text:".to_string(),
+            r##"    def split(self, sep="#", maxsplit=-1):"##.to_string(),
+            "        pass".to_string(),
+            "    def pad(self,  # the text".to_string(),
+            "            fill='(') -> str:".to_string(),
+            "        pass".to_string(),
+        ];
+        let methods = extract_methods(test_codebase);
+
+        assert_eq!(methods.len(), 2);
+        assert_eq!(methods[0].name, "split");
+        assert_eq!(methods[1].name, "pad");
+        assert_eq!(methods[1].output, "str");
+    }
+
     #[test]
     fn test_extract_method_output() {
         let test_string = String::from("def this_name(self, param2: int) -> List[int]:");
@@ -345,14 +746,14 @@ mod tests {
             "",
         ];
 
-        let keyword = String::from("God");
+        let matcher = Matcher::new("God", MatchMode::Plain, false).unwrap();
         let filename = "./testing";
         let expected = vec![
-            (String::from("class God:"), filename.to_string()),
-            (String::from("class GodMode:"), filename.to_string()),
+            ClassMatch::new(String::from("class God:"), filename.to_string(), 100, vec![(6, 9)]),
+            ClassMatch::new(String::from("class GodMode:"), filename.to_string(), 80, vec![(6, 9)]),
         ];
 
-        assert_eq!(grep_class(test_codebase, &keyword, filename).unwrap(), expected);
+        assert_eq!(grep_lines(test_codebase, &matcher, filename), expected);
     }
 
     #[test]
@@ -370,11 +771,91 @@ mod tests {
             "",
         ];
 
-        let keyword = String::from("Zeus");
+        let matcher = Matcher::new("Zeus", MatchMode::Plain, false).unwrap();
         let filename = "./testing";
 
 
-        assert_eq!(grep_class(test_codebase, &keyword, filename), None);
+        assert!(grep_lines(test_codebase, &matcher, filename).is_empty());
+    }
+
+    #[test]
+    fn test_grep_class_fuzzy_ignores_bases() {
+        let test_codebase = vec![
+            "class InvoiceService(BaseService):",
+            "    pass",
+            "",
+            "class Invoice(Model):",
+            "    pass",
+        ];
+
+        let matcher = Matcher::new("invsvc", MatchMode::Fuzzy, false).unwrap();
+        let matches = grep_lines(test_codebase, &matcher, "./testing");
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].header, "class InvoiceService(BaseService):");
+        assert_eq!(matches[0].spans[0], (6, 9));
+    }
+
+    #[test]
+    fn test_grep_class_empty_keyword() {
+        let test_codebase = vec![
+            "class InvoiceService(BaseService):",
+            "    pass",
+        ];
+
+        let matcher = Matcher::new("", MatchMode::Fuzzy, false).unwrap();
+
+        assert_eq!(grep_lines(test_codebase, &matcher, "./testing").len(), 1);
+        assert_eq!(matcher.find("InvoiceService"), None);
+    }
+
+    #[test]
+    fn test_grep_methods() {
+        let test_code = "class Ledger:\n    def reconcile(self, day: date) -> bool:\n        pass\n\n    def close(self):\n        pass\n";
+        let source = SourceFile::new(String::from("./ledger.py"), test_code.to_string());
+        let indexed = index_source(&source).remove(0);
+
+        let matcher = Matcher::new("reconcile", MatchMode::Plain, false).unwrap();
+        let matches = grep_methods(indexed, &matcher);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].class_name, "Ledger");
+        assert_eq!(matches[0].method.signature(), "reconcile(self, day: date) -> bool");
+        assert_eq!(matches[0].location, "./ledger.py:2");
+        assert_eq!(matches[0].score, 100);
+    }
+
+    #[test]
+    fn test_mentions_type() {
+        assert!(mentions_type("Optional[Session]", "Session", false));
+        assert!(mentions_type("db.Session", "Session", false));
+        assert!(mentions_type("Optional[ 'User' ]", "Optional[User]", false));
+        assert!(mentions_type("dict[str, session]", "Session", true));
+        assert!(!mentions_type("SessionFactory", "Session", false));
+        assert!(!mentions_type("List[User]", "Optional[User]", false));
+    }
+
+    #[test]
+    fn test_grep_types() {
+        let test_code = "class Repo:\n    def load(self, session: Session, key: str) -> Optional[User]:\n        pass\n";
+        let source = SourceFile::new(String::from("./repo.py"), test_code.to_string());
+
+        let matches = grep_types(index_source(&source).remove(0), "Session", false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].usage(), "Repo.load(session: Session)");
+        assert_eq!(matches[0].location, "./repo.py:2");
+
+        let matches = grep_types(index_source(&source).remove(0), "Optional[User]", false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].usage(), "Repo.load() -> Optional[User]");
+    }
+
+    #[test]
+    fn test_extract_class_name() {
+        let test_header = "    class Human(Being):";
+
+        assert_eq!(extract_class_name(test_header), Some((10, "Human")));
+        assert_eq!(extract_class_name("def human():"), None);
     }
 
     #[test]
@@ -385,6 +866,79 @@ mod tests {
         assert_eq!(extract_class_inheritance(&test_header), Some(expected));
     }
 
+    #[test]
+    fn test_extract_decorators() {
+        let test_codebase = vec![
+            "import registry",
+            "@registry.register(name='human')",
+            "  @dataclass",
+            "class Human:",
+        ];
+        let expected = vec![
+            String::from("registry.register(name='human')"),
+            String::from("dataclass"),
+        ];
+
+        assert_eq!(extract_decorators(&test_codebase, 3), expected);
+        assert_eq!(decorator_name(&expected[0]), "register");
+        assert!(extract_decorators(&test_codebase, 1).is_empty());
+    }
+
+    #[test]
+    fn test_extract_multiline_decorators() {
+        let test_codebase = vec![
+            "VALUE = (1,",
+            "    2)",
+            "@registry.register(",
+            "    name='human',",
+            "    tags=['a', 'b'],",
+            ")",
+            "@dataclass",
+            "class Human:",
+        ];
+        let expected = vec![
+            String::from("registry.register(name='human', tags=['a', 'b'],)"),
+            String::from("dataclass"),
+        ];
+
+        assert_eq!(extract_decorators(&test_codebase, 7), expected);
+        assert_eq!(decorator_name(&expected[0]), "register");
+    }
+
+    #[test]
+    fn test_extract_class_keywords() {
+        let test_header = String::from("class Human(Being, metaclass=Singleton):");
+        let expected = vec![(String::from("metaclass"), String::from("Singleton"))];
+
+        assert_eq!(extract_class_keywords(&test_header), expected);
+    }
+
+    #[test]
+    fn test_extract_attributes() {
+        let test_codebase: Vec<String> = [
+            "class Invoice(Model):",
+            "    \"\"\"",
+            "    total = computed",
+            "    \"\"\"",
+            "    table_name = 'invoices'",
+            "    pass",
+            "    total: Decimal",
+            "    def __init__(self, number: str):",
+            "        self.number: str = number",
+            "        self.lines = []",
+            "        if self.total == 0:",
+            "            self.table_name = 'drafts'",
+        ].iter().map(|line| line.to_string()).collect();
+        let expected = vec![
+            Parameter::new(String::from("table_name"), String::from("None")),
+            Parameter::new(String::from("total"), String::from("Decimal")),
+            Parameter::new(String::from("number"), String::from("str")),
+            Parameter::new(String::from("lines"), String::from("None")),
+        ];
+
+        assert_eq!(extract_attributes(&test_codebase), expected);
+    }
+
     #[test]
     fn test_extract_no_inheritance(){
         let test_header = String::from("class Human:");
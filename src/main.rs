@@ -8,26 +8,327 @@ License: MIT
 Copyright 2021 Vlad Nedelcu
 */
 
-mod joneslib;
 mod commands;
 
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
+use jones::joneslib;
 use joneslib::display;
+use joneslib::matcher::{Matcher, MatchMode};
+use joneslib::query::Query;
+use joneslib::project::Project;
+use joneslib::sources::SearchPath;
+use joneslib::objects::PythonClass;
+use joneslib::hierarchy;
+use joneslib::diagram;
+use joneslib::usages;
+use joneslib::history;
+use joneslib::calls::CallGraph;
+use joneslib::changes::{diff_classes, diff_projects, json_report};
+use commands::Command;
+
+const WORKING_DIRECTORY: &str = "working directory";
+const BREAKING_EXIT_CODE: i32 = 1;
+const ERROR_EXIT_CODE: i32 = 2;
 
 fn main() {
     let comms = commands::CLI::from_args();
-    if comms.grep {
-        // Search for a keyword in class name
-        match joneslib::smart_search(&comms.dir_path, &comms.class_name) {
-            Some(matches) => display::class_matches(matches),
+    if let Some(command) = &comms.command {
+        return run_command(command)
+    }
+
+    let class_name = comms.class_name.as_deref().unwrap_or_default();
+    let search_path = match comms.search_path() {
+        Ok(search_path) => search_path,
+        Err(err) => return display::error_message(&err)
+    };
+    if comms.ancestors {
+        // Resolve the inheritance chain of a class
+        match load_class(&search_path, class_name) {
+            Ok((project, class_id)) => display::ancestors(&project.classes[class_id], &project.ancestors(class_id)),
+            Err(err) => display::error_message(&err)
+        }
+    } else if comms.subclasses {
+        // Find every class inheriting the class
+        match load_class(&search_path, class_name) {
+            Ok((project, class_id)) => display::subclasses(&project.classes[class_id], &project.subclasses(class_id)),
+            Err(err) => display::error_message(&err)
+        }
+    } else if let Some(format) = comms.diagram {
+        // Export a class diagram of a class or a package
+        let project = match Project::load(&search_path) {
+            Ok(project) => project,
+            Err(err) => return display::error_message(&err)
+        };
+        match diagram::select_classes(&project, class_name) {
+            Some(class_ids) => println!("{}", diagram::render(&project, &class_ids, format)),
             None => display::not_found_message()
         }
-    } else {
-        // Generate python class
-        match joneslib::project_traversal(&comms.dir_path, &comms.class_name) {
-            Some(class) => joneslib::display::output_class(&class),
+    } else if comms.calls {
+        // Build the call graph of a class or of one of its methods
+        let project = match Project::load(&search_path) {
+            Ok(project) => project,
+            Err(err) => return display::error_message(&err)
+        };
+        let methods = match project.find_methods(class_name) {
+            Some(methods) => methods,
+            None => return display::not_found_message()
+        };
+        let graph = CallGraph::new(&project);
+        match comms.dot {
+            true => println!("{}", graph.render_dot(&project, &methods)),
+            false => display::calls(&project, &graph, &methods)
+        }
+    } else if comms.function {
+        // Inspect module level functions and constants
+        let project = match Project::load(&search_path) {
+            Ok(project) => project,
+            Err(err) => return display::error_message(&err)
+        };
+        let functions = project.find_functions(class_name);
+        let constants = project.find_constants(class_name);
+        match functions.is_empty() && constants.is_empty() {
+            true => display::not_found_message(),
+            false => display::functions(&functions, &constants)
+        }
+    } else if comms.query {
+        // Evaluate a query expression over all classes
+        let query = match Query::parse(class_name) {
+            Ok(query) => query,
+            Err(err) => return display::error_message(&err)
+        };
+        match joneslib::query_search(&search_path, &query) {
+            Some(classes) => display::queried_classes(classes),
             None => display::not_found_message()
         }
+    } else if comms.doc {
+        // Search for a text inside docstrings
+        match joneslib::doc_search(&search_path, class_name) {
+            Some(docs) => display::doc_matches(docs),
+            None => display::not_found_message()
+        }
+    } else if comms.type_search {
+        // Search for methods accepting or returning a type
+        match joneslib::type_search(&search_path, class_name, comms.ignore_case) {
+            Some(types) => display::type_matches(types),
+            None => display::not_found_message()
+        }
+    } else if comms.grep || comms.smart || comms.method {
+        let mode = if comms.regex {
+            MatchMode::Regex
+        } else if comms.fuzzy {
+            MatchMode::Fuzzy
+        } else {
+            MatchMode::Plain
+        };
+        let matcher = match Matcher::new(class_name, mode, comms.ignore_case || comms.smart) {
+            Ok(matcher) => matcher,
+            Err(err) => return display::error_message(&err)
+        };
+
+        if comms.method {
+            // Search for classes defining a method
+            match joneslib::method_search(&search_path, &matcher) {
+                Some(methods) => display::method_matches(methods),
+                None => display::not_found_message()
+            }
+        } else if comms.smart {
+            // Rank classes by keyword and context
+            match joneslib::smart_search(&search_path, &matcher) {
+                Some(ranked) => display::ranked_classes(ranked),
+                None => display::not_found_message()
+            }
+        } else {
+            // Search for a keyword in class name
+            let filter = match comms.grep_filter() {
+                Ok(filter) => filter,
+                Err(err) => return display::error_message(&err)
+            };
+            match joneslib::grep_search(&search_path, &matcher, &filter) {
+                Some(matches) => display::class_matches(matches),
+                None => display::not_found_message()
+            }
+        }
+    } else if comms.inherited {
+        // Generate python class with the methods of its ancestors
+        let (project, class_id) = match load_class(&search_path, class_name) {
+            Ok(found) => found,
+            Err(err) => return display::error_message(&err)
+        };
+        match hierarchy::linearize(&project, class_id) {
+            Ok(mro) => {
+                let groups = hierarchy::method_groups(&project, &mro);
+                let root = project_root(&search_path, &project, class_id);
+                display::output_class(&project.classes[class_id], import_path(&project, class_id).as_deref(), root, Some((&project, &groups)))
+            },
+            Err(err) => display::error_message(&err)
+        }
+    } else {
+        // Generate python class with the path it is imported from, indexing
+        // the whole project only if no module file defines the class
+        if let Some((project, class_id)) = joneslib::lookup_class(&search_path, class_name) {
+            let root = project_root(&search_path, &project, class_id);
+            return display::output_class(&project.classes[class_id], import_path(&project, class_id).as_deref(), root, None)
+        }
+        let project = match Project::load(&search_path) {
+            Ok(project) => project,
+            Err(err) => return display::error_message(&err)
+        };
+        match project.find_class(class_name) {
+            Some(class_id) => {
+                let root = project_root(&search_path, &project, class_id);
+                display::output_class(&project.classes[class_id], import_path(&project, class_id).as_deref(), root, None)
+            },
+            None => {
+                // Fall back to the module level functions and constants
+                let functions = project.find_functions(class_name);
+                let constants = project.find_constants(class_name);
+                match functions.is_empty() && constants.is_empty() {
+                    true => display::not_found_message(),
+                    false => display::functions(&functions, &constants)
+                }
+            }
+        }
+    }
+}
+
+/// Indexes the project and finds a class in it
+///
+/// # Errors
+///
+/// Returns an error if the project could not be read or does not define the class
+fn load_class(search_path: &SearchPath, class_name: &str) -> Result<(Project, usize), String> {
+    let project = Project::load(search_path)?;
+    match project.find_class(class_name) {
+        Some(class_id) => Ok((project, class_id)),
+        None => Err(format!("Class {} was not found in the project", class_name))
+    }
+}
+
+/// The ready to paste import of a class, which a notebook class has none of
+fn import_path(project: &Project, class_id: usize) -> Option<String> {
+    match project.module_of(class_id).is_notebook() {
+        true => None,
+        false => Some(project.import_path(class_id))
+    }
+}
+
+/// The search root of a class, shown only when several roots are searched
+fn project_root<'a>(search_path: &SearchPath, project: &'a Project, class_id: usize) -> Option<&'a Path> {
+    match search_path.roots.len() > 1 {
+        true => Some(project.root_of(class_id)),
+        false => None
+    }
+}
+
+/// Runs a command working on the whole project
+fn run_command(command: &Command) {
+    match command {
+        Command::Usages { class_name, search, rev, dir_paths } => {
+            // Find where the class is used across the project
+            let search_path = match search.search_path(dir_paths, rev.as_deref()) {
+                Ok(search_path) => search_path,
+                Err(err) => return display::error_message(&err)
+            };
+            match load_class(&search_path, class_name) {
+                Ok((project, class_id)) => display::usages(&project.classes[class_id], &usages::find_usages(&project, class_id)),
+                Err(err) => display::error_message(&err)
+            }
+        },
+        Command::Module { module, search, rev, dir_paths } => {
+            // Print the table of contents of a module
+            let search_path = match search.search_path(dir_paths, rev.as_deref()) {
+                Ok(search_path) => search_path,
+                Err(err) => return display::error_message(&err)
+            };
+            let project = match Project::load(&search_path) {
+                Ok(project) => project,
+                Err(err) => return display::error_message(&err)
+            };
+            let module = search_path.roots.iter()
+                .find_map(|root| Path::new(module).strip_prefix(root).ok())
+                .map(|relative| relative.to_string_lossy().to_string())
+                .unwrap_or_else(|| module.to_string());
+            match project.find_module(&module) {
+                Some(module_id) => display::module_overview(&project, module_id),
+                None => display::error_message("Searched module was not found in project")
+            }
+        },
+        Command::Diff { class_name, from, to, dir_paths } => api_diff(class_name, (from, to.as_deref()), dir_paths),
+        Command::Breaking { from, to, json, dir_paths } => breaking_changes((from, to.as_deref()), *json, dir_paths),
+        Command::History { class_name, search, dir_paths } => {
+            // Find when each method of the class was introduced and last changed
+            let search_path = match search.search_path(dir_paths, None) {
+                Ok(search_path) => search_path,
+                Err(err) => return display::error_message(&err)
+            };
+            let (project, class_id) = match load_class(&search_path, class_name) {
+                Ok(found) => found,
+                Err(err) => return display::error_message(&err)
+            };
+            match history::class_history(&project.classes[class_id]) {
+                Ok(methods) => display::method_history(&project.classes[class_id], &methods),
+                Err(err) => display::error_message(&err)
+            }
+        }
     }
 }
 
+/// Compares the API of the class between two versions
+fn api_diff(class_name: &str, (from, to): (&str, Option<&str>), dir_paths: &[PathBuf]) {
+    let to_label = to.unwrap_or(WORKING_DIRECTORY);
+    let old = match version_class(class_name, Some(from), from, dir_paths) {
+        Ok(old) => old,
+        Err(err) => return display::error_message(&err)
+    };
+    let new = match version_class(class_name, to, to_label, dir_paths) {
+        Ok(new) => new,
+        Err(err) => return display::error_message(&err)
+    };
+    display::api_changes(class_name, (from, to_label), &diff_classes(&old, &new))
+}
+
+/// Compares the public API between two versions, failing on breaking changes
+fn breaking_changes((from, to): (&str, Option<&str>), json: bool, dir_paths: &[PathBuf]) {
+    let to_label = to.unwrap_or(WORKING_DIRECTORY);
+    let projects = version_project(Some(from), dir_paths)
+        .and_then(|old| Ok((old, version_project(to, dir_paths)?)));
+    let (old, new) = match projects {
+        Ok(projects) => projects,
+        Err(err) => {
+            display::error_message(&err);
+            std::process::exit(ERROR_EXIT_CODE)
+        }
+    };
+    let reports = diff_projects(&old, &new);
+    match json {
+        true => println!("{}", json_report((from, to_label), &reports)),
+        false => display::breaking_changes((from, to_label), &reports)
+    }
+    if reports.iter().flat_map(|report| report.changes.iter()).any(|change| change.is_breaking()) {
+        std::process::exit(BREAKING_EXIT_CODE)
+    }
+}
+
+/// Loads a version of the project, given as a git revision or a directory
+///
+/// # Errors
+///
+/// Returns an error if the version could not be read
+fn version_project(version: Option<&str>, dir_paths: &[PathBuf]) -> Result<Project, String> {
+    let search_path = commands::version_search_path(version, dir_paths)?;
+    Project::load(&search_path)
+}
+
+/// Loads a class from a version of the project, given as a git revision or a directory
+///
+/// # Errors
+///
+/// Returns an error if the version could not be read or does not define the class
+fn version_class(class_name: &str, version: Option<&str>, label: &str, dir_paths: &[PathBuf]) -> Result<PythonClass, String> {
+    let mut project = version_project(version, dir_paths)?;
+    match project.find_class(class_name) {
+        Some(class_id) => Ok(project.classes.swap_remove(class_id).class),
+        None => Err(format!("Class {} was not found in {}", class_name, label))
+    }
+}